@@ -0,0 +1,432 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use frame_support::{
+	pallet_prelude::*,
+	traits::{Currency, ExistenceRequirement, ReservableCurrency},
+};
+use frame_system::pallet_prelude::*;
+use log;
+use scale_info::TypeInfo;
+use sp_core::{
+	crypto::KeyTypeId,
+	ecdsa, ed25519, sr25519,
+	offchain::Duration,
+};
+use sp_io::hashing::blake2_256;
+use sp_runtime::{
+	offchain::storage_lock::{StorageLock, Time},
+	traits::{IdentifyAccount, Saturating, SaturatedConversion, Verify, Zero},
+	AccountId32, MultiSignature, MultiSigner,
+};
+use sp_std::vec::Vec;
+
+pub const LOG_TARGET: &'static str = "runtime::iris-ledger";
+/// how long (in milliseconds) an OCW run's auto-claim lock for an account stays held before it
+/// expires; a run that finds the lock already held skips that account for the tick at once,
+/// rather than risk double-submitting
+const OCW_LOCK_TIMEOUT_EXPIRATION: u64 = 50_000;
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"lgkt");
+
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// identifies one of an account's concurrent locks in `Ledger`, so e.g. funds committed to two
+/// different storage deals can be tracked, and later released, independently of each other
+pub type ReservationId = u64;
+
+/// A linear unlock schedule for an account's `Ledger` entry: `locked` is released at a
+/// constant rate of `per_block` starting at `starting_block`, mirroring `pallet-vesting`'s
+/// schedule shape.
+#[derive(PartialEq, Eq, Clone, Default, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct VestingSchedule<Balance, BlockNumber> {
+	pub locked: Balance,
+	pub per_block: Balance,
+	pub starting_block: BlockNumber,
+}
+
+/// One of an account's keystore-resident keys, tagged with the curve it was generated under.
+/// Lets the OCW author `submit_auto_claim_signed` submissions for operators running ed25519 or
+/// ecdsa keys rather than assuming sr25519, which is all `Signer::<T, T::AuthorityId>` can do
+/// once a runtime has committed to a single `AuthorityId` crypto type.
+pub enum OcwSigner {
+	Sr25519(sr25519::Public),
+	Ed25519(ed25519::Public),
+	Ecdsa(ecdsa::Public),
+}
+
+impl OcwSigner {
+	/// the `AccountId` this key derives to: `MultiSigner::into_account()` for sr25519/ed25519,
+	/// and blake2-256 over the compressed public key for ecdsa, mirroring `MultiSigner`'s own
+	/// `IdentifyAccount` impl for that variant
+	pub fn account_id(&self) -> AccountId32 {
+		match self {
+			OcwSigner::Sr25519(public) => MultiSigner::Sr25519(*public).into_account(),
+			OcwSigner::Ed25519(public) => MultiSigner::Ed25519(*public).into_account(),
+			OcwSigner::Ecdsa(public) => blake2_256(public.as_ref()).into(),
+		}
+	}
+
+	/// sign `payload` under this key via the runtime's keystore, returning a `MultiSignature`
+	/// of the matching variant, or `None` if the key is not present in the local keystore
+	pub fn sign_payload(&self, payload: &[u8]) -> Option<MultiSignature> {
+		match self {
+			OcwSigner::Sr25519(public) =>
+				sp_io::crypto::sr25519_sign(KEY_TYPE, public, payload).map(MultiSignature::Sr25519),
+			OcwSigner::Ed25519(public) =>
+				sp_io::crypto::ed25519_sign(KEY_TYPE, public, payload).map(MultiSignature::Ed25519),
+			OcwSigner::Ecdsa(public) =>
+				sp_io::crypto::ecdsa_sign(KEY_TYPE, public, payload).map(MultiSignature::Ecdsa),
+		}
+	}
+
+	/// every `KEY_TYPE` key present in the local keystore, across all three supported curves
+	pub fn local_keys() -> impl Iterator<Item = OcwSigner> {
+		sp_io::crypto::sr25519_public_keys(KEY_TYPE).into_iter().map(OcwSigner::Sr25519)
+			.chain(sp_io::crypto::ed25519_public_keys(KEY_TYPE).into_iter().map(OcwSigner::Ed25519))
+			.chain(sp_io::crypto::ecdsa_public_keys(KEY_TYPE).into_iter().map(OcwSigner::Ecdsa))
+	}
+
+	/// the local keystore's `KEY_TYPE` key for `who`, if it holds one, tried across all three
+	/// supported curves rather than just whatever single curve `T::AuthorityId` is bound to
+	pub fn for_account(who: &AccountId32) -> Option<OcwSigner> {
+		Self::local_keys().find(|signer| &signer.account_id() == who)
+	}
+}
+
+/// A payload submitted by an offchain worker auto-claiming `who`'s vested balance under
+/// reservation `id`, signed by one of `who`'s own [`KEY_TYPE`] keys via [`OcwSigner`] instead of
+/// `T::AuthorityId`'s fixed curve. Verified directly against the embedded [`AccountId32`]
+/// rather than through the single-curve `SignedPayload`/`AppCrypto` machinery, since
+/// `MultiSignature::verify` already authenticates all three supported curves against an
+/// `AccountId32` signer.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct AutoClaimPayload<BlockNumber> {
+	pub who: AccountId32,
+	pub id: ReservationId,
+	pub block_number: BlockNumber,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_system::{
+		pallet_prelude::*,
+		offchain::{
+			AppCrypto,
+			CreateSignedTransaction,
+			SubmitTransaction,
+		},
+	};
+
+	#[pallet::config]
+	// `AccountId = AccountId32` because `OcwSigner::account_id` (and the `AutoClaimPayload` it
+	// signs over) always derives an `AccountId32`, regardless of which of the three supported
+	// curves signed it.
+	pub trait Config: CreateSignedTransaction<Call<Self>> + frame_system::Config<AccountId = AccountId32> {
+		/// The Event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// the currency reserved into, and paid out of, the ledger
+		type Currency: ReservableCurrency<Self::AccountId>;
+		/// the authority id used for sending signed txs
+		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// an account's locked balances, split by [`ReservationId`] so e.g. funds committed to two
+	/// different storage deals can be released independently; the portion of a reservation
+	/// still subject to a `Vesting` schedule cannot be moved by `unlock_reservation`
+	#[pallet::storage]
+	#[pallet::getter(fn ledger)]
+	pub type Ledger<T: Config> = StorageDoubleMap<
+		_, Blake2_128Concat, T::AccountId, Blake2_128Concat, ReservationId, BalanceOf<T>, OptionQuery,
+	>;
+
+	/// the next [`ReservationId`] to be handed out by `lock` or `vested_transfer`
+	#[pallet::storage]
+	#[pallet::getter(fn next_reservation_id)]
+	pub type NextReservationId<T: Config> = StorageValue<_, ReservationId, ValueQuery>;
+
+	/// a reservation's outstanding vesting schedule, if it was funded through `vested_transfer`
+	/// rather than `lock`
+	#[pallet::storage]
+	#[pallet::getter(fn vesting)]
+	pub type Vesting<T: Config> = StorageDoubleMap<
+		_, Blake2_128Concat, T::AccountId, Blake2_128Concat, ReservationId,
+		VestingSchedule<BalanceOf<T>, T::BlockNumber>, OptionQuery,
+	>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// an account locked funds into a new ledger reservation
+		Locked(T::AccountId, ReservationId, BalanceOf<T>),
+		/// a reservation's vested balance was unlocked and transferred to a beneficiary
+		UnlockedAndTransferred(T::AccountId, ReservationId, T::AccountId, BalanceOf<T>),
+		/// a vested transfer created a gradual unlock schedule under a new reservation
+		VestingScheduleCreated(T::AccountId, ReservationId, BalanceOf<T>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// the caller has no reservation under the given [`ReservationId`]
+		UnknownReservation,
+		/// the reservation has no vested (withdrawable) balance yet
+		InsufficientVestedBalance,
+		/// an `submit_auto_claim_signed` payload's signature didn't match its claimed account
+		BadProof,
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		/// `submit_auto_claim_signed` carries an [`AutoClaimPayload`] signed by one of its own
+		/// `who`'s [`KEY_TYPE`] keys via [`OcwSigner`] -- any of the three supported curves, not
+		/// just whatever single curve `T::AuthorityId` is bound to -- so it's verified directly
+		/// against the embedded `AccountId32` rather than through the single-curve
+		/// `SignedPayload`/`AppCrypto` machinery the pallet's other OCW submissions would use.
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			if let Call::submit_auto_claim_signed { payload, signature } = call {
+				if !signature.verify(&payload.encode()[..], &payload.who) {
+					return InvalidTransaction::BadProof.into();
+				}
+				ValidTransaction::with_tag_prefix("iris-ledger::auto-claim")
+					.and_provides((payload.who.clone(), payload.id))
+					.longevity(5)
+					.propagate(true)
+					.build()
+			} else {
+				InvalidTransaction::Call.into()
+			}
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn offchain_worker(_block_number: T::BlockNumber) {
+			let now = <frame_system::Pallet<T>>::block_number();
+			for (who, id, _schedule) in <Vesting<T>>::iter() {
+				let total_locked = match <Ledger<T>>::get(&who, id) {
+					Some(amount) => amount,
+					None => continue,
+				};
+				let still_locked = Self::vested_balance(&who, id, now).min(total_locked);
+				if still_locked >= total_locked {
+					// nothing has vested yet; nothing to auto-claim
+					continue;
+				}
+				Self::submit_auto_claim(who, id);
+			}
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Reserve `amount` from the caller's free balance and credit it to a new ledger
+		/// reservation, withdrawable in full at any time via `unlock_reservation`. Returns (via
+		/// the `Locked` event) the [`ReservationId`] the caller must pass to release it.
+		#[pallet::weight(100)]
+		pub fn lock(origin: OriginFor<T>, amount: BalanceOf<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			T::Currency::reserve(&who, amount)?;
+			let id = Self::allocate_reservation_id();
+			<Ledger<T>>::insert(&who, id, amount);
+			Self::deposit_event(Event::Locked(who, id, amount));
+			Ok(())
+		}
+
+		/// Unlock the caller's currently-vested balance under reservation `id` and transfer it
+		/// to `target`, leaving the caller's other reservations untouched. Funds still subject
+		/// to an outstanding [`VestingSchedule`] are left locked under `id`.
+		#[pallet::weight(100)]
+		pub fn unlock_reservation(
+			origin: OriginFor<T>,
+			id: ReservationId,
+			target: T::AccountId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_unlock(&who, id, &target)
+		}
+
+		/// Confirm an offchain worker's auto-claim of `payload.who`'s own vested balance under
+		/// `payload.id`, authenticated by `signature` from one of `payload.who`'s own
+		/// [`KEY_TYPE`] keys rather than a funded account signing a regular extrinsic -- lets an
+		/// operator running ed25519 or ecdsa keys auto-claim without being forced onto whatever
+		/// single curve `T::AuthorityId` is bound to. See `validate_unsigned` for the signature
+		/// check; this always self-targets, unlike the general `unlock_reservation`.
+		#[pallet::weight(100)]
+		pub fn submit_auto_claim_signed(
+			origin: OriginFor<T>,
+			payload: AutoClaimPayload<T::BlockNumber>,
+			_signature: MultiSignature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			Self::do_unlock(&payload.who, payload.id, &payload.who)
+		}
+
+		/// Move `schedule.locked` from the caller to a new reservation under `target`, releasing
+		/// it linearly at `schedule.per_block` per block starting at `schedule.starting_block`
+		/// instead of making it withdrawable all at once.
+		#[pallet::weight(100)]
+		pub fn vested_transfer(
+			origin: OriginFor<T>,
+			target: T::AccountId,
+			schedule: VestingSchedule<BalanceOf<T>, T::BlockNumber>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			T::Currency::transfer(&who, &target, schedule.locked, ExistenceRequirement::AllowDeath)?;
+			T::Currency::reserve(&target, schedule.locked)?;
+			let id = Self::allocate_reservation_id();
+			<Ledger<T>>::insert(&target, id, schedule.locked);
+			Self::deposit_event(Event::VestingScheduleCreated(target.clone(), id, schedule.locked));
+			<Vesting<T>>::insert(&target, id, schedule);
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// shared between `unlock_reservation`'s self-service path and
+		/// `submit_auto_claim_signed`'s OCW-driven path: release `who`'s currently-vested
+		/// balance under reservation `id` and transfer it to `target`, leaving funds still
+		/// subject to an outstanding [`VestingSchedule`] locked under `id`.
+		fn do_unlock(who: &T::AccountId, id: ReservationId, target: &T::AccountId) -> DispatchResult {
+			let total_locked = <Ledger<T>>::get(who, id).ok_or(Error::<T>::UnknownReservation)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			let still_locked = Self::vested_balance(who, id, now).min(total_locked);
+			let withdrawable = total_locked.saturating_sub(still_locked);
+			ensure!(withdrawable > Zero::zero(), Error::<T>::InsufficientVestedBalance);
+
+			T::Currency::unreserve(who, withdrawable);
+			T::Currency::transfer(who, target, withdrawable, ExistenceRequirement::AllowDeath)?;
+			if still_locked.is_zero() {
+				<Ledger<T>>::remove(who, id);
+				<Vesting<T>>::remove(who, id);
+			} else {
+				<Ledger<T>>::insert(who, id, still_locked);
+			}
+			Self::deposit_event(Event::UnlockedAndTransferred(who.clone(), id, target.clone(), withdrawable));
+			Ok(())
+		}
+
+		/// the portion of reservation `id` that is still locked at block `now`, i.e.
+		/// `locked.saturating_sub(per_block * (now - starting_block))` clamped at zero. A
+		/// reservation with no [`Vesting`] schedule has nothing still locked.
+		pub fn vested_balance(who: &T::AccountId, id: ReservationId, now: T::BlockNumber) -> BalanceOf<T> {
+			let schedule = match <Vesting<T>>::get(who, id) {
+				Some(schedule) => schedule,
+				None => return Zero::zero(),
+			};
+			if now <= schedule.starting_block {
+				return schedule.locked;
+			}
+			let elapsed: BalanceOf<T> = now.saturating_sub(schedule.starting_block).saturated_into();
+			schedule.locked.saturating_sub(schedule.per_block.saturating_mul(elapsed))
+		}
+
+		/// `who`'s total locked balance across all of its reservations, derived by summing
+		/// `Ledger`'s per-reservation entries; kept for callers that only need the account-wide
+		/// total and don't care how it's split.
+		pub fn total_locked(who: &T::AccountId) -> BalanceOf<T> {
+			<Ledger<T>>::iter_prefix(who).fold(Zero::zero(), |total, (_, amount)| total.saturating_add(amount))
+		}
+
+		/// hand out the next [`ReservationId`], bumping the counter so concurrent `lock` and
+		/// `vested_transfer` calls never collide
+		fn allocate_reservation_id() -> ReservationId {
+			let id = Self::next_reservation_id();
+			<NextReservationId<T>>::put(id.wrapping_add(1));
+			id
+		}
+
+		/// for a reservation whose vesting schedule has freed up some balance: submit an
+		/// `submit_auto_claim_signed` on `who`'s behalf, signed by whichever of `who`'s own
+		/// [`KEY_TYPE`] keys is present in this node's local keystore -- across all three
+		/// supported curves via [`OcwSigner`], not just whatever single curve `T::AuthorityId`
+		/// is bound to. Acquires a per-account [`StorageLock`] first so two OCW runs racing on
+		/// the same tick don't submit the same claim twice.
+		///
+		/// An earlier version of this submission replayed a signed extrinsic, de-duplicated by a
+		/// per-account nonce persisted under `b"iris/ocw/nonce/"`. That tracking is gone, not
+		/// dropped by accident: `submit_auto_claim_signed` is dispatched as an unsigned call
+		/// authenticated through `validate_unsigned`, and unsigned calls carry no account nonce
+		/// to read or bump. Replay protection now comes from `and_provides((who, id))` in
+		/// `validate_unsigned`, which the transaction pool itself uses to drop a resubmission of
+		/// the same reservation while one is already in flight; the `StorageLock` above still
+		/// guards the purely local race of two OCW runs on this node issuing it twice.
+		fn submit_auto_claim(who: T::AccountId, id: ReservationId) {
+			let lock_key = Self::ocw_lock_key(&who);
+			let mut lock = StorageLock::<Time>::with_deadline(
+				&lock_key,
+				Duration::from_millis(OCW_LOCK_TIMEOUT_EXPIRATION),
+			);
+			let _guard = match lock.try_lock() {
+				Ok(guard) => guard,
+				Err(_) => {
+					log::debug!(
+						target: LOG_TARGET,
+						"OCW auto-claim lock for {:?} is held by another run; skipping this tick",
+						who,
+					);
+					return;
+				},
+			};
+
+			let signer = match OcwSigner::for_account(&who) {
+				Some(signer) => signer,
+				None => {
+					log::debug!(
+						target: LOG_TARGET,
+						"no local keystore key for {:?}; skipping auto-claim", who,
+					);
+					return;
+				},
+			};
+
+			let block_number = <frame_system::Pallet<T>>::block_number();
+			let payload = AutoClaimPayload { who: who.clone(), id, block_number };
+			let signature = match signer.sign_payload(&payload.encode()) {
+				Some(signature) => signature,
+				None => {
+					log::error!(
+						target: LOG_TARGET,
+						"failed to sign auto-claim payload for {:?} reservation {:?}",
+						who, id,
+					);
+					return;
+				},
+			};
+
+			let call = Call::submit_auto_claim_signed { payload, signature };
+			match SubmitTransaction::<T, Call<T>>::submit_transaction(call.into()) {
+				Ok(()) => log::info!(
+					target: LOG_TARGET,
+					"auto-claimed vested balance for {:?} reservation {:?}",
+					who, id,
+				),
+				Err(()) => log::error!(
+					target: LOG_TARGET,
+					"failed to auto-claim vested balance for {:?} reservation {:?}",
+					who, id,
+				),
+			}
+		}
+
+		fn ocw_lock_key(who: &T::AccountId) -> Vec<u8> {
+			let mut key = b"iris/ocw/lock/".to_vec();
+			key.extend_from_slice(&who.encode());
+			key
+		}
+	}
+}
@@ -0,0 +1,169 @@
+use crate as iris_ledger;
+use crate::{Config, KEY_TYPE};
+use frame_support::parameter_types;
+use frame_system as system;
+use sp_core::{sr25519::Signature as Sr25519Signature, H256};
+use sp_keystore::{testing::KeyStore, KeystoreExt, SyncCryptoStore};
+use sp_runtime::{
+	testing::{Header, TestXt},
+	traits::{BlakeTwo256, IdentifyAccount, IdentityLookup, Verify},
+	AccountId32, MultiSigner,
+};
+use std::sync::Arc;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		IrisLedger: iris_ledger::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId32;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+	pub const MaxLocks: u32 = 50;
+}
+
+impl pallet_balances::Config for Test {
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type MaxLocks = MaxLocks;
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type WeightInfo = ();
+}
+
+impl<LocalCall> system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = TestXt<Call, ()>;
+}
+
+impl<LocalCall> system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	fn create_transaction<C: system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: Call,
+		_public: Self::Public,
+		_account: AccountId32,
+		_nonce: u64,
+	) -> Option<(Call, <Self::Extrinsic as sp_runtime::traits::Extrinsic>::SignaturePayload)> {
+		Some((call, ()))
+	}
+}
+
+impl system::offchain::SigningTypes for Test {
+	type Public = <Sr25519Signature as Verify>::Signer;
+	type Signature = Sr25519Signature;
+}
+
+/// this pallet's own [`KEY_TYPE`] identity, wired up for the mock runtime the way
+/// iris-session's `crypto` module wires up `TestAuthId` for its own mock
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_core::sr25519::Signature as Sr25519Signature;
+	use sp_runtime::app_crypto::{app_crypto, sr25519};
+	use sp_runtime::traits::Verify;
+
+	app_crypto!(sr25519, KEY_TYPE);
+
+	pub struct TestAuthId;
+	impl frame_system::offchain::AppCrypto<<Sr25519Signature as Verify>::Signer, Sr25519Signature>
+		for TestAuthId
+	{
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+}
+
+impl Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type AuthorityId = crypto::TestAuthId;
+}
+
+/// a bare test externality with no pre-funded accounts
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut ext: sp_io::TestExternalities =
+		system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// a bare test externality with a keystore attached but no pre-funded accounts, for tests that
+/// need to generate their own `KEY_TYPE` keys (e.g. an ed25519/ecdsa `OcwSigner` round trip) and
+/// fund the resulting account afterwards via `Currency::deposit_creating`
+pub fn new_test_ext_with_keystore() -> sp_io::TestExternalities {
+	let mut ext: sp_io::TestExternalities =
+		system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
+	ext.register_extension(KeystoreExt(Arc::new(KeyStore::new())));
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// a test externality funded per `balances`, with an empty keystore attached: the funded
+/// accounts' keys are not inserted into it, so `OcwSigner` finds none of them --
+/// `balances` is a list of (account, free balance) pairs keyed by the sr25519 public key the
+/// test generated the account from
+pub fn new_test_ext_funded(balances: Vec<(sp_core::sr25519::Public, u64)>) -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: balances
+			.iter()
+			.map(|(public, amount)| (MultiSigner::Sr25519(*public).into_account(), *amount))
+			.collect(),
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	let keystore = KeyStore::new();
+	ext.register_extension(KeystoreExt(Arc::new(keystore)));
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -1,36 +1,29 @@
-use super::*;
-use frame_support::{assert_ok};
-use mock::*;
+use crate::mock::*;
+use crate::{AutoClaimPayload, Error, Ledger, OcwSigner, VestingSchedule, Vesting, KEY_TYPE};
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok, traits::Currency};
 use sp_core::Pair;
-use sp_core::{
-	offchain::{testing, OffchainWorkerExt, TransactionPoolExt, OffchainDbExt}
-};
-use sp_keystore::{testing::KeyStore, KeystoreExt, SyncCryptoStore};
-use std::sync::Arc;
+use sp_runtime::{traits::IdentifyAccount, MultiSigner};
+
+fn account(public: &sp_core::sr25519::Public) -> sp_core::crypto::AccountId32 {
+	MultiSigner::Sr25519(*public).into_account()
+}
 
 #[test]
 fn iris_ledger_initial_state() {
 	new_test_ext().execute_with(|| {
-		// Given: The node is initialized at block 0
-		// When: I query runtime storagey
-		let ledger = crate::Ledger::<Test>::get();
-		let len = ledger.len();
-		// Then: Runtime storage is empty
-		assert_eq!(len, 0);
+		let (p, _) = sp_core::sr25519::Pair::generate();
+		assert_eq!(Ledger::<Test>::get(account(&p.public()), 0), None);
 	});
 }
 
 #[test]
 fn iris_ledger_can_lock() {
 	let (p, _) = sp_core::sr25519::Pair::generate();
-	let pairs = vec![(p.clone().public(), 10)];
+	let pairs = vec![(p.public(), 10)];
 	new_test_ext_funded(pairs).execute_with(|| {
-		assert_ok!(
-			Origin::signed(p.clone().public()),
-			1,
-		);
-		let mut locked_amount = crate::Ledger::<Test>::get(p.public().clone());
-		assert_eq!(1, locked_amount);
+		assert_ok!(IrisLedger::lock(Origin::signed(account(&p.public())), 1));
+		assert_eq!(Ledger::<Test>::get(account(&p.public()), 0), Some(1));
 	});
 }
 
@@ -39,18 +32,136 @@ fn iris_ledger_can_unlock_and_transfer() {
 	let (p, _) = sp_core::sr25519::Pair::generate();
 	let (p2, _) = sp_core::sr25519::Pair::generate();
 
-	let pairs = vec![(p.clone().public(), 10)];
+	let pairs = vec![(p.public(), 10)];
 	new_test_ext_funded(pairs).execute_with(|| {
-		assert_ok!(
-			Origin::signed(p.clone().public()),
-			1,
+		assert_ok!(IrisLedger::lock(Origin::signed(account(&p.public())), 1));
+		assert_ok!(IrisLedger::unlock_reservation(
+			Origin::signed(account(&p.public())),
+			0,
+			account(&p2.public()),
+		));
+		assert_eq!(Ledger::<Test>::get(account(&p.public()), 0), None);
+		assert_eq!(Balances::free_balance(account(&p2.public())), 1);
+	});
+}
+
+#[test]
+fn iris_ledger_unlock_reservation_fails_for_unknown_reservation() {
+	let (p, _) = sp_core::sr25519::Pair::generate();
+	new_test_ext_funded(vec![(p.public(), 10)]).execute_with(|| {
+		assert_noop!(
+			IrisLedger::unlock_reservation(Origin::signed(account(&p.public())), 0, account(&p.public())),
+			Error::<Test>::UnknownReservation,
 		);
-		
-		assert_ok!(
-			Origin::signed(p.clone().public()),
-			p2.clone().public(),
+	});
+}
+
+#[test]
+fn iris_ledger_vested_transfer_leaves_unvested_balance_locked() {
+	let (p, _) = sp_core::sr25519::Pair::generate();
+	let (target, _) = sp_core::sr25519::Pair::generate();
+
+	new_test_ext_funded(vec![(p.public(), 10)]).execute_with(|| {
+		let schedule = VestingSchedule { locked: 10, per_block: 1, starting_block: 0u64 };
+		assert_ok!(IrisLedger::vested_transfer(
+			Origin::signed(account(&p.public())),
+			account(&target.public()),
+			schedule,
+		));
+		assert_eq!(Ledger::<Test>::get(account(&target.public()), 0), Some(10));
+
+		// only 5 of the 10 blocks' worth have vested by block 5
+		System::set_block_number(5);
+		assert_ok!(IrisLedger::unlock_reservation(
+			Origin::signed(account(&target.public())),
+			0,
+			account(&target.public()),
+		));
+		assert_eq!(Ledger::<Test>::get(account(&target.public()), 0), Some(5));
+		assert_eq!(Balances::free_balance(account(&target.public())), 5);
+		assert!(Vesting::<Test>::get(account(&target.public()), 0).is_some());
+	});
+}
+
+#[test]
+fn iris_ledger_unlock_reservation_fails_before_anything_has_vested() {
+	let (p, _) = sp_core::sr25519::Pair::generate();
+	let (target, _) = sp_core::sr25519::Pair::generate();
+
+	new_test_ext_funded(vec![(p.public(), 10)]).execute_with(|| {
+		let schedule = VestingSchedule { locked: 10, per_block: 1, starting_block: 100u64 };
+		assert_ok!(IrisLedger::vested_transfer(
+			Origin::signed(account(&p.public())),
+			account(&target.public()),
+			schedule,
+		));
+		assert_noop!(
+			IrisLedger::unlock_reservation(
+				Origin::signed(account(&target.public())),
+				0,
+				account(&target.public()),
+			),
+			Error::<Test>::InsufficientVestedBalance,
 		);
-		let mut locked_amount = crate::Ledger::<Test>::get(p.public().clone());
-		assert_eq!(0, locked_amount);
 	});
-}
\ No newline at end of file
+}
+
+#[test]
+fn iris_ledger_tracks_concurrent_reservations_independently() {
+	let (p, _) = sp_core::sr25519::Pair::generate();
+	new_test_ext_funded(vec![(p.public(), 10)]).execute_with(|| {
+		assert_ok!(IrisLedger::lock(Origin::signed(account(&p.public())), 3));
+		assert_ok!(IrisLedger::lock(Origin::signed(account(&p.public())), 4));
+		assert_eq!(Ledger::<Test>::get(account(&p.public()), 0), Some(3));
+		assert_eq!(Ledger::<Test>::get(account(&p.public()), 1), Some(4));
+
+		assert_ok!(IrisLedger::unlock_reservation(
+			Origin::signed(account(&p.public())),
+			0,
+			account(&p.public()),
+		));
+		assert_eq!(Ledger::<Test>::get(account(&p.public()), 0), None);
+		assert_eq!(Ledger::<Test>::get(account(&p.public()), 1), Some(4));
+	});
+}
+
+#[test]
+fn iris_ledger_submit_auto_claim_signed_accepts_an_ed25519_ocw_signer() {
+	new_test_ext_with_keystore().execute_with(|| {
+		let public = sp_io::crypto::ed25519_generate(KEY_TYPE, None);
+		let signer = OcwSigner::Ed25519(public);
+		let who = signer.account_id();
+
+		let _ = Balances::deposit_creating(&who, 10);
+		assert_ok!(IrisLedger::lock(Origin::signed(who.clone()), 1));
+
+		let payload = AutoClaimPayload { who: who.clone(), id: 0, block_number: 1u64 };
+		let signature = signer.sign_payload(&payload.encode()).expect("key is in the keystore");
+
+		assert_ok!(IrisLedger::submit_auto_claim_signed(Origin::none(), payload, signature));
+		// the reservation is gone and the claimed balance is back in `who`'s free balance --
+		// the point of this test is that an ed25519-only account can drive the whole flow,
+		// not the arithmetic of a self-targeted claim
+		assert_eq!(Ledger::<Test>::get(&who, 0), None);
+		assert_eq!(Balances::free_balance(&who), 10);
+	});
+}
+
+#[test]
+fn iris_ledger_submit_auto_claim_signed_unlocks_on_who_s_behalf() {
+	let (p, _) = sp_core::sr25519::Pair::generate();
+	new_test_ext_funded(vec![(p.public(), 10)]).execute_with(|| {
+		assert_ok!(IrisLedger::lock(Origin::signed(account(&p.public())), 1));
+
+		let payload = AutoClaimPayload { who: account(&p.public()), id: 0, block_number: 1u64 };
+		let signature = sp_core::sr25519::Pair::sign(&p, &payload.encode());
+
+		assert_ok!(IrisLedger::submit_auto_claim_signed(
+			Origin::none(),
+			payload,
+			signature.into(),
+		));
+		assert_eq!(Ledger::<Test>::get(account(&p.public()), 0), None);
+		assert_eq!(Balances::free_balance(account(&p.public())), 10);
+	});
+}
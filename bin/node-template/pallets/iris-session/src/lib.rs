@@ -25,22 +25,29 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(test)]
 mod mock;
+#[cfg(test)]
 mod tests;
 
 use frame_support::{
 	ensure,
 	pallet_prelude::*,
 	traits::{
-		EstimateNextSessionRotation, Get,
+		tokens::fungible::{Inspect, Mutate},
+		Currency, EstimateNextSessionRotation, Get,
 		ValidatorSet, ValidatorSetWithIdentification,
 	},
+	PalletId,
 };
 use log;
 use scale_info::TypeInfo;
 pub use pallet::*;
-use sp_runtime::traits::{Convert, Zero};
-use sp_staking::offence::{Offence, OffenceError, ReportOffence};
+use sp_runtime::{traits::{AccountIdConversion, Convert, Hash, Zero}, Perbill};
+use sp_staking::{
+	offence::{Kind, Offence, OffenceError, ReportOffence},
+	SessionIndex,
+};
 use sp_std::{
 	collections::{ btree_set::BTreeSet, btree_map::BTreeMap },
 	str,
@@ -55,27 +62,48 @@ use sp_core::{
     Bytes,
 };
 use frame_system::{
-	self as system, 
+	self as system,
+	ensure_none,
 	ensure_signed,
 	offchain::{
 		SendSignedTransaction,
+		SendUnsignedTransaction,
+		SignedPayload,
 		Signer,
-		SubmitTransaction,
+		SigningTypes,
 	}
 };
-use sp_io::offchain::timestamp;
+use sp_io::{
+	hashing::blake2_256,
+	offchain::timestamp,
+};
 use sp_runtime::{
-	offchain::ipfs,
-	traits::StaticLookup,
+	offchain::{
+		ipfs,
+		storage_lock::{BlockAndTime, StorageLock},
+	},
+	traits::{IdentifyAccount, StaticLookup},
 };
 use pallet_iris_assets::{
 	DataCommand,
 };
+use chacha20::{
+	ChaCha20,
+	cipher::{NewCipher, StreamCipher},
+};
 
 pub const LOG_TARGET: &'static str = "runtime::iris-session";
 // TODO: should a new KeyTypeId be defined? e.g. b"iris"
 pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"aura");
 
+/// key for the offchain storage lock guarding the IPFS offchain worker's queue-processing
+/// against concurrent/overlapping ticks (e.g. a `CatBytes`/`AddBytes` fetch that outlives a block)
+pub const IPFS_WORKER_LOCK: &[u8] = b"iris::ipfs-worker-lock";
+/// number of blocks the IPFS worker lock may be held before it's considered stale
+pub const IPFS_WORKER_LOCK_BLOCK_EXPIRATION: u32 = 3;
+/// time (in milliseconds) the IPFS worker lock may be held before it's considered stale
+pub const IPFS_WORKER_LOCK_TIMEOUT_EXPIRATION: u64 = 10_000;
+
 pub mod crypto {
 	use crate::KEY_TYPE;
 	use sp_core::sr25519::Signature as Sr25519Signature;
@@ -106,6 +134,9 @@ pub mod crypto {
 pub type EraIndex = u32;
 /// counter for the number of "reward" points earned by a given storage provider
 pub type RewardPoint = u32;
+/// balance type used by this pallet's `Currency` handle
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
 /// Reward points for storage providers of some specific assest id during an era.
 #[derive(PartialEq, Encode, Decode, Default, RuntimeDebug, TypeInfo)]
@@ -128,6 +159,286 @@ pub struct ActiveEraInfo {
 	start: Option<u64>,
 }
 
+/// Offence kind for a storage provider that has repeatedly failed to prove replication or
+/// otherwise serve its storage duties.
+pub const STORAGE_UNAVAILABILITY_OFFENCE_ID: Kind = *b"iris:storage-dwn";
+
+/// An offence raised when a storage provider's `UnproductiveSessions` count exceeds
+/// `MaxDeadSession`, or it has missed or failed `MaxReplicationFaults` consecutive
+/// proof-of-replication challenges for some asset. The slash fraction scales with
+/// `consecutive_faults`, which folds in both causes.
+#[derive(PartialEq, Eq, Clone, RuntimeDebug)]
+pub struct StorageUnavailabilityOffence<AccountId> {
+	/// the session the offence was reported in
+	pub session_index: SessionIndex,
+	/// the size of the validator set at the time of reporting
+	pub validator_set_count: u32,
+	/// the offending storage providers
+	pub offenders: Vec<AccountId>,
+	/// unproductive sessions beyond `MaxDeadSession`, plus outstanding `ReplicationFaults`
+	/// across all assets, the offenders have accrued
+	pub consecutive_faults: u32,
+}
+
+impl<AccountId: Clone> Offence<AccountId> for StorageUnavailabilityOffence<AccountId> {
+	const ID: Kind = STORAGE_UNAVAILABILITY_OFFENCE_ID;
+	type TimeSlot = SessionIndex;
+
+	fn offenders(&self) -> Vec<AccountId> {
+		self.offenders.clone()
+	}
+
+	fn session_index(&self) -> SessionIndex {
+		self.session_index
+	}
+
+	fn validator_set_count(&self) -> u32 {
+		self.validator_set_count
+	}
+
+	fn time_slot(&self) -> Self::TimeSlot {
+		self.session_index
+	}
+
+	fn slash_fraction(&self, _offenders_count: u32) -> Perbill {
+		// 10% per consecutive era of failed duties, capped at 100%
+		Perbill::from_percent((self.consecutive_faults.min(10)) * 10)
+	}
+}
+
+/// A signed-over payload reporting an embedded IPFS node's identity, following the
+/// im-online heartbeat pattern: the offchain worker signs this with its `AuthorityId` key so
+/// `validate_unsigned` can authenticate the submission without a funded signed account.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct IdentityPayload<Public, BlockNumber> {
+	pub public_key: Vec<u8>,
+	pub multiaddresses: Vec<OpaqueMultiaddr>,
+	pub block_number: BlockNumber,
+	pub public: Public,
+}
+
+impl<T: SigningTypes> SignedPayload<T> for IdentityPayload<T::Public, T::BlockNumber> {
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// A signed-over payload reporting that some asset's data has been made available over RPC,
+/// authenticated the same way as [`IdentityPayload`].
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct RpcReadyPayload<Public, BlockNumber, AssetId> {
+	pub asset_id: AssetId,
+	pub block_number: BlockNumber,
+	pub public: Public,
+}
+
+impl<T: Config> SignedPayload<T> for RpcReadyPayload<T::Public, T::BlockNumber, T::AssetId> {
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// A signed-over payload carrying a validator's re-encrypted share of an encrypted asset's
+/// content key, submitted for a specific requestor once its `CatBytes` access has been
+/// authorized. Authenticated the same way as [`IdentityPayload`].
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct KeyShareSubmissionPayload<Public, BlockNumber, AssetId, AccountId> {
+	pub asset_id: AssetId,
+	pub requestor: AccountId,
+	pub share: Vec<u8>,
+	pub block_number: BlockNumber,
+	pub public: Public,
+}
+
+impl<T: Config> SignedPayload<T>
+	for KeyShareSubmissionPayload<T::Public, T::BlockNumber, T::AssetId, T::AccountId>
+{
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// A signed-over payload declaring that enough key shares have been collected for a requestor
+/// to reconstruct an encrypted asset's content key. Authenticated the same way as
+/// [`IdentityPayload`].
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct DecryptionReadyPayload<Public, BlockNumber, AssetId, AccountId> {
+	pub asset_id: AssetId,
+	pub requestor: AccountId,
+	pub block_number: BlockNumber,
+	pub public: Public,
+}
+
+impl<T: Config> SignedPayload<T>
+	for DecryptionReadyPayload<T::Public, T::BlockNumber, T::AssetId, T::AccountId>
+{
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// A signed-over payload confirming that a queued CID was published under an asset's IPNS
+/// name. Authenticated the same way as [`IdentityPayload`]: only a current validator's
+/// `AuthorityId` signature over the payload is accepted, so an asset's served content can't be
+/// hijacked by a throwaway signed account claiming to be the reporting offchain worker.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct PublishUpdateResultPayload<Public, BlockNumber, AssetId> {
+	pub asset_id: AssetId,
+	pub ipns_name: Vec<u8>,
+	pub block_number: BlockNumber,
+	pub public: Public,
+}
+
+impl<T: Config> SignedPayload<T>
+	for PublishUpdateResultPayload<T::Public, T::BlockNumber, T::AssetId>
+{
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// A signed-over payload confirming that a leaving storage provider's pin was removed from the
+/// local IPFS node. Authenticated the same way as [`IdentityPayload`].
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct UnpinResultPayload<Public, BlockNumber, AssetId, AccountId> {
+	pub asset_id: AssetId,
+	pub pinner: AccountId,
+	pub block_number: BlockNumber,
+	pub public: Public,
+}
+
+impl<T: Config> SignedPayload<T>
+	for UnpinResultPayload<T::Public, T::BlockNumber, T::AssetId, T::AccountId>
+{
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// Number of pseudo-random segments a CID's content is considered to be divided into
+/// for the purpose of proof-of-replication sampling.
+pub const CHALLENGE_SEGMENT_SPACE: u32 = 256;
+
+/// An outstanding proof-of-replication challenge issued to a storage provider for some asset.
+///
+/// The provider must prove, by `deadline_block` (inclusive), that it still holds the bytes behind the
+/// asset's CID by fetching them and submitting the raw `segment_index`-th slice plus its Merkle
+/// inclusion path against `ContentSegmentRoot` via `submit_porep_proof`. Submitting only a hash
+/// of the segment would be just as cheap to precompute for all `CHALLENGE_SEGMENT_SPACE`
+/// segments as for the one actually challenged, so the chain insists on the bytes themselves.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct ReplicationChallenge<BlockNumber> {
+	/// per-era, per-provider sampling seed
+	pub seed: [u8; 32],
+	/// pseudo-random segment selected for this challenge (see `CHALLENGE_SEGMENT_SPACE`)
+	pub segment_index: u32,
+	/// the block by which a proof must be submitted
+	pub deadline_block: BlockNumber,
+}
+
+/// Disk usage of the embedded IPFS node's repo, as reported by an `IpfsRequest::StorageStats`
+/// round-trip. Returned by the `node_storage_stats` RPC runtime API and consulted before this
+/// node volunteers itself as a storage provider for an under-replicated asset.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct NodeStorageStats {
+	/// bytes still free under the repo's configured storage max
+	pub available_storage: u64,
+	/// the repo's configured storage max, in bytes
+	pub maximum_storage: u64,
+	/// number of files pinned locally that this node is directly responsible for
+	pub files: usize,
+	/// total number of files tracked in the local repo, including indirectly pinned blocks
+	pub total_files: usize,
+}
+
+/// the pure Merkle-tree math behind `submit_porep_proof`'s segment-proof verification, factored
+/// out from `impl<T: Config> Pallet<T>` and generic only over the hasher (not the full pallet
+/// `Config`, which pulls in `pallet_session`/`pallet_iris_assets`) so it can be unit tested
+/// directly against a concrete hasher like `BlakeTwo256` without standing up a mock runtime.
+pub(crate) mod merkle {
+	use super::CHALLENGE_SEGMENT_SPACE;
+	use sp_runtime::traits::Hash;
+	use sp_std::vec::Vec;
+
+	/// the raw bytes of `data`'s `index`-th (of `CHALLENGE_SEGMENT_SPACE`) slice, used both to
+	/// build `ContentSegmentRoot`'s leaves at registration time and to answer a PoRep challenge
+	/// for that index later.
+	pub fn segment_bytes(data: &[u8], index: usize) -> Vec<u8> {
+		let segment_count = CHALLENGE_SEGMENT_SPACE as usize;
+		// ceil-divide so every byte is covered even when `data.len()` isn't a multiple of
+		// `segment_count`; the trailing slices are shorter, or empty once the data runs out.
+		let segment_len = (data.len() + segment_count - 1) / segment_count.max(1);
+		let segment_len = segment_len.max(1);
+		let start = (index * segment_len).min(data.len());
+		let end = (start + segment_len).min(data.len());
+		data[start..end].to_vec()
+	}
+
+	/// split `data` into `CHALLENGE_SEGMENT_SPACE` slices of `segment_bytes`'s size (trailing
+	/// slices may be shorter or empty) and hash each one, giving the leaves of the Merkle tree
+	/// committed to as `ContentSegmentRoot`.
+	pub fn segment_leaves<H: Hash>(data: &[u8]) -> Vec<H::Output> {
+		let segment_count = CHALLENGE_SEGMENT_SPACE as usize;
+		(0..segment_count).map(|i| H::hash(&segment_bytes(data, i))).collect()
+	}
+
+	/// combine pairs of Merkle tree nodes into their parent, one level at a time, until a single
+	/// root hash remains. Relies on `CHALLENGE_SEGMENT_SPACE` being a power of two so every
+	/// level divides evenly in half.
+	pub fn merkle_root_from_leaves<H: Hash>(leaves: &[H::Output]) -> H::Output {
+		let mut level = leaves.to_vec();
+		while level.len() > 1 {
+			level = level.chunks(2).map(|pair| H::hash_of(&(pair[0], pair[1]))).collect();
+		}
+		level.into_iter().next().unwrap_or_default()
+	}
+
+	/// the Merkle root over the actual plaintext content behind an asset's CID, computed by an
+	/// offchain worker that has fetched it. Stored as `ContentSegmentRoot` at registration time
+	/// so `submit_porep_proof` can later verify a segment-level proof without the chain ever
+	/// needing to read the content itself.
+	pub fn segment_merkle_root<H: Hash>(data: &[u8]) -> H::Output {
+		merkle_root_from_leaves::<H>(&segment_leaves::<H>(data))
+	}
+
+	/// the sibling hash at each level needed to recompute `root` from the leaf at `index`,
+	/// bottom level first. `submit_porep_proof` walks this back up via `verify_segment_proof`.
+	pub fn segment_merkle_proof<H: Hash>(leaves: &[H::Output], mut index: usize) -> Vec<H::Output> {
+		let mut level = leaves.to_vec();
+		let mut proof = Vec::new();
+		while level.len() > 1 {
+			proof.push(level[index ^ 1]);
+			level = level.chunks(2).map(|pair| H::hash_of(&(pair[0], pair[1]))).collect();
+			index /= 2;
+		}
+		proof
+	}
+
+	/// recompute a Merkle root from a challenged `leaf` at `index` plus its sibling `proof`, and
+	/// check it matches the root committed to at content-registration time. Only a node that
+	/// actually fetched the segment's bytes (and so can produce both the correct leaf hash and,
+	/// implicitly, a root whose siblings were derived from the real content) can pass this --
+	/// unlike a single whole-content hash, individual leaves can't be recovered from `root`
+	/// alone, so nothing here is derivable from already-public chain state.
+	pub fn verify_segment_proof<H: Hash>(
+		leaf: H::Output,
+		mut index: usize,
+		proof: &[H::Output],
+		root: H::Output,
+	) -> bool {
+		let computed = proof.iter().fold(leaf, |node, sibling| {
+			let parent = if index % 2 == 0 {
+				H::hash_of(&(node, *sibling))
+			} else {
+				H::hash_of(&(*sibling, node))
+			};
+			index /= 2;
+			parent
+		});
+		computed == root
+	}
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -160,6 +471,46 @@ pub mod pallet {
 		type MaxDeadSession: Get<u32>;
 		/// the authority id used for sending signed txs
         type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+		/// number of blocks a storage provider has to respond to a proof-of-replication challenge
+		type ChallengeWindow: Get<Self::BlockNumber>;
+		/// number of consecutive missed or failed proof-of-replication challenges, for any one
+		/// asset, a storage provider may accrue before being reported as a
+		/// `StorageUnavailabilityOffence`
+		type MaxReplicationFaults: Get<u32>;
+		/// handler for reporting storage-unavailability offences to the slashing backend
+		type ReportOffence: ReportOffence<
+			Self::AccountId,
+			Self::AccountId,
+			StorageUnavailabilityOffence<Self::AccountId>,
+		>;
+		/// the currency used to pay out era rewards to storage providers
+		type Currency: Currency<Self::AccountId>;
+		/// number of eras for which a payout can still be claimed
+		type HistoryDepth: Get<u32>;
+		/// the total reward budget allocated to an era
+		type EraPayout: Get<BalanceOf<Self>>;
+		/// the default number of storage providers an asset should be replicated across when
+		/// no asset-specific `ReplicationFactor` has been set
+		type DefaultReplicationFactor: Get<u32>;
+		/// the fraction of an asset's candidate storage providers that must independently
+		/// confirm a pin before it is treated as finalized and rewarded
+		type PinConfirmationThreshold: Get<Perbill>;
+		/// the fungible token handle used to slash offending storage providers, separate from
+		/// `Currency` since slashing is expressed in terms of `fungible::Mutate` rather than the
+		/// legacy `Currency` trait
+		type Fungible: Inspect<Self::AccountId, Balance = BalanceOf<Self>>
+			+ Mutate<Self::AccountId, Balance = BalanceOf<Self>>;
+		/// the pallet id of the treasury-style account that receives slashed bonds
+		type SlashTreasuryId: Get<PalletId>;
+		/// the bond assumed to be staked/reserved by a storage provider, the amount
+		/// `slash_fraction` is taken out of when an offence is reported
+		type SlashableBond: Get<BalanceOf<Self>>;
+		/// the minimum free disk space, in bytes, a node must report via `node_storage_stats`
+		/// before it will volunteer itself as a storage provider for an under-replicated asset
+		type MinFreeStorageBytes: Get<u64>;
+		/// the number of validator key shares (`k` of the Shamir `k`-of-`n` split) that must be
+		/// collected for a requestor before an encrypted asset's content key can be reconstructed
+		type KeyShareThreshold: Get<u32>;
 	}
 
 	#[pallet::pallet]
@@ -263,8 +614,172 @@ pub mod pallet {
 		_, Blake2_128Concat, T::AccountId, u32, ValueQuery,
 	>;
 
+	/// the blake2-256 hash of the plaintext content behind an asset's CID, reported by the
+	/// offchain worker that originally added it.
+	#[pallet::storage]
+	#[pallet::getter(fn content_hash)]
+	pub type ContentHashes<T: Config> = StorageMap<
+		_, Blake2_128Concat, T::AssetId, T::Hash, ValueQuery,
+	>;
+
+	/// the root of a binary Merkle tree over `CHALLENGE_SEGMENT_SPACE` leaf hashes, each the
+	/// blake2-256 of one `1 / CHALLENGE_SEGMENT_SPACE` slice of the plaintext content behind an
+	/// asset's CID, reported by the offchain worker that originally added it. Used as the basis
+	/// for PoRep challenges: unlike `ContentHashes`, a leaf can't be recovered from the root
+	/// alone, so answering a challenge for a given segment requires actually having fetched that
+	/// segment's bytes rather than just reading already-public chain state.
+	#[pallet::storage]
+	#[pallet::getter(fn content_segment_root)]
+	pub type ContentSegmentRoot<T: Config> = StorageMap<
+		_, Blake2_128Concat, T::AssetId, T::Hash, ValueQuery,
+	>;
+
+	/// outstanding proof-of-replication challenges, keyed by asset id and challenged provider
+	#[pallet::storage]
+	#[pallet::getter(fn storage_challenges)]
+	pub type StorageChallenges<T: Config> = StorageDoubleMap<
+		_, Blake2_128Concat, T::AssetId, Blake2_128Concat, T::AccountId,
+		ReplicationChallenge<T::BlockNumber>, OptionQuery,
+	>;
+
+	/// count of consecutive failed/missed replication challenges per asset, per provider
+	#[pallet::storage]
+	#[pallet::getter(fn replication_faults)]
+	pub type ReplicationFaults<T: Config> = StorageDoubleMap<
+		_, Blake2_128Concat, T::AssetId, Blake2_128Concat, T::AccountId, u32, ValueQuery,
+	>;
+
+	/// the total reward budget allocated to an era, claimable via `payout_stakers` until the
+	/// era falls outside of `HistoryDepth`.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_validator_reward)]
+	pub type ErasValidatorReward<T: Config> = StorageMap<
+		_, Blake2_128Concat, EraIndex, BalanceOf<T>, OptionQuery,
+	>;
+
+	/// the sum of every asset's `EraRewardPoints::total` for an era, snapshotted by the first
+	/// `payout_stakers` call against that era. `ErasRewardPoints` entries are removed as each
+	/// asset is paid out, so this can't be recomputed from `ErasRewardPoints::iter_prefix` on
+	/// later calls without shrinking the denominator out from under assets that haven't claimed
+	/// yet; caching it here keeps every asset's share of `budget` stable regardless of payout order.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_total_reward_points)]
+	pub type ErasTotalRewardPoints<T: Config> = StorageMap<
+		_, Blake2_128Concat, EraIndex, u32, OptionQuery,
+	>;
+
+	/// storage providers that have voluntarily suspended serving duties via `go_offline`.
+	/// They are skipped by challenge issuance and are not penalized for unproductive sessions.
+	#[pallet::storage]
+	#[pallet::getter(fn offline_intent)]
+	pub type OfflineIntent<T: Config> = StorageMap<
+		_, Blake2_128Concat, T::AccountId, bool, ValueQuery,
+	>;
+
+	/// asset/provider pairs awaiting an IPFS unpin after a provider called `leave_storage_pool`
+	#[pallet::storage]
+	#[pallet::getter(fn pending_unpins)]
+	pub type PendingUnpins<T: Config> = StorageDoubleMap<
+		_, Blake2_128Concat, T::AssetId, Blake2_128Concat, T::AccountId, (), OptionQuery,
+	>;
+
+	/// the replication target for an asset, i.e. the number of storage providers the network
+	/// tries to maintain for it. Falls back to `T::DefaultReplicationFactor` when unset.
+	#[pallet::storage]
+	#[pallet::getter(fn replication_factor)]
+	pub type ReplicationFactor<T: Config> = StorageMap<
+		_, Blake2_128Concat, T::AssetId, u32, OptionQuery,
+	>;
+
+	/// assets currently providing fewer storage providers than their replication target,
+	/// mapped to the size of the shortfall. Populated in `select_candidate_storage_providers`
+	/// and consumed by the offchain worker to auto-enqueue replacement providers.
+	#[pallet::storage]
+	#[pallet::getter(fn replication_deficit)]
+	pub type ReplicationDeficit<T: Config> = StorageMap<
+		_, Blake2_128Concat, T::AssetId, u32, ValueQuery,
+	>;
+
+	/// the account that originally submitted `submit_ipfs_add_results` for an asset, used to
+	/// fill in `pool_owner` when the offchain worker auto-enqueues a replacement provider
+	#[pallet::storage]
+	#[pallet::getter(fn asset_owner)]
+	pub type AssetOwners<T: Config> = StorageMap<
+		_, Blake2_128Concat, T::AssetId, T::AccountId, OptionQuery,
+	>;
+
+	/// the distinct validators that have independently attested (via `submit_ipfs_pin_result`)
+	/// that a candidate has pinned an asset's CID during the current era. Cleared once the
+	/// pin is finalized (`T::PinConfirmationThreshold` reached) or the era rotates.
+	#[pallet::storage]
+	#[pallet::getter(fn pin_confirmations)]
+	pub type PinConfirmations<T: Config> = StorageDoubleMap<
+		_, Blake2_128Concat, T::AssetId, Blake2_128Concat, T::AccountId, Vec<T::AccountId>, ValueQuery,
+	>;
+
+	/// offences already slashed through `ReportOffence::report_offence`, keyed by a hash of the
+	/// offending accounts and the offence's time slot, so a duplicate report of the same offence
+	/// in the same session is a no-op instead of a double slash
+	#[pallet::storage]
+	#[pallet::getter(fn known_offences)]
+	pub type KnownOffences<T: Config> = StorageMap<
+		_, Blake2_128Concat, T::Hash, (), OptionQuery,
+	>;
+
+	/// signed payloads (from the im-online-style `validate_unsigned` calls) that have already
+	/// been applied on-chain, keyed by a hash of the payload and its signature, so a captured
+	/// payload replayed in a later block is rejected instead of re-awarding its reward points
+	#[pallet::storage]
+	#[pallet::getter(fn processed_payloads)]
+	pub type ProcessedPayloads<T: Config> = StorageMap<
+		_, Blake2_128Concat, T::Hash, (), OptionQuery,
+	>;
+
+	/// marks an asset as holding Shamir-split-key encrypted content rather than plaintext;
+	/// gates the `CatBytes` flow into key-share reconstruction instead of a plaintext
+	/// `rpc_ready` broadcast
+	#[pallet::storage]
+	#[pallet::getter(fn is_encrypted_asset)]
+	pub type EncryptedAssets<T: Config> = StorageMap<
+		_, Blake2_128Concat, T::AssetId, (), OptionQuery,
+	>;
+
+	/// the submitter's initial `k`-of-`n` split of an encrypted asset's content key, one opaque
+	/// share per active validator at registration time, each encrypted under that validator's
+	/// `AuthorityId` public key so only that validator can recover its own share
+	#[pallet::storage]
+	#[pallet::getter(fn validator_key_shares)]
+	pub type ValidatorKeyShares<T: Config> = StorageDoubleMap<
+		_, Blake2_128Concat, T::AssetId, Blake2_128Concat, T::AccountId, Vec<u8>, OptionQuery,
+	>;
+
+	/// shares validators have recovered and re-encrypted for a specific requestor, accumulated
+	/// until `T::KeyShareThreshold` is reached and the requestor can reconstruct the content key
+	#[pallet::storage]
+	#[pallet::getter(fn requestor_key_shares)]
+	pub type RequestorKeyShares<T: Config> = StorageDoubleMap<
+		_, Blake2_128Concat, T::AssetId, Blake2_128Concat, T::AccountId,
+		Vec<(T::AccountId, Vec<u8>)>, ValueQuery,
+	>;
+
+	/// the IPNS key name a pinning validator publishes an asset's current CID under, letting the
+	/// asset's underlying content be versioned without minting a new asset id
+	#[pallet::storage]
+	#[pallet::getter(fn ipns_names)]
+	pub type IpnsNames<T: Config> = StorageMap<
+		_, Blake2_128Concat, T::AssetId, Vec<u8>, OptionQuery,
+	>;
+
+	/// a new CID an asset's owner has queued to be published under the asset's IPNS name,
+	/// drained by the offchain worker in `handle_publish_updates`
+	#[pallet::storage]
+	#[pallet::getter(fn pending_publish_updates)]
+	pub type PendingPublishUpdates<T: Config> = StorageMap<
+		_, Blake2_128Concat, T::AssetId, Vec<u8>, OptionQuery,
+	>;
+
+	///
 	///
-	/// 
 	// #[pallet::storage]
 	// #[pallet::getter(fn dead_validator)]
 	// pub type DeadValidators<T: Config> = StorageMap<
@@ -281,6 +796,41 @@ pub mod pallet {
 		PublishedIdentity(T::AccountId),
 		/// A validator requested to join a storage pool
 		RequestJoinStoragePoolSuccess(T::AccountId, T::AssetId),
+		/// A proof-of-replication challenge was issued to a storage provider
+		ChallengeIssued(T::AccountId, T::AssetId),
+		/// A storage provider successfully answered a proof-of-replication challenge
+		ChallengeFulfilled(T::AccountId, T::AssetId),
+		/// A storage provider failed to answer a proof-of-replication challenge
+		ChallengeFailed(T::AccountId, T::AssetId),
+		/// A storage provider was reported for a storage-unavailability offence and slashed
+		StorageProviderSlashed(T::AccountId),
+		/// A storage provider was paid out their share of an era's reward budget
+		Rewarded(T::AccountId, BalanceOf<T>),
+		/// A storage provider left a storage pool of their own accord
+		LeftStoragePool(T::AccountId, T::AssetId),
+		/// A storage provider voluntarily suspended serving duties
+		WentOffline(T::AccountId),
+		/// A storage provider resumed serving duties after a voluntary suspension
+		WentOnline(T::AccountId),
+		/// A storage provider's pin was successfully removed after leaving a storage pool
+		UnpinSuccess(T::AccountId, T::AssetId),
+		/// An asset dropped below its replication target; carries the current shortfall
+		ReplicationUnderTarget(T::AssetId, u32),
+		/// Enough distinct validators confirmed a pin for `PinConfirmationThreshold` to be met;
+		/// the candidate is now an official pinner and has been rewarded
+		PinConfirmed(T::AssetId, T::AccountId),
+		/// An asset was registered as holding encrypted content, split across active validators
+		EncryptedAssetRegistered(T::AccountId, T::AssetId),
+		/// A validator submitted its re-encrypted content-key share for a requestor
+		KeyShareSubmitted(T::AssetId, T::AccountId, T::AccountId),
+		/// Enough key shares were collected for a requestor to reconstruct an encrypted
+		/// asset's content key
+		DecryptionReady(T::AssetId, T::AccountId),
+		/// An asset's owner queued a new CID to be published under the asset's IPNS name
+		PublishUpdateQueued(T::AssetId, T::AccountId),
+		/// A new CID was published under an asset's IPNS name, making it the asset's current
+		/// content without changing the asset id or its access grants
+		PublishUpdateApplied(T::AssetId),
 	}
 
 	
@@ -290,11 +840,24 @@ pub mod pallet {
 
 		/// Validate unsigned call to this module.
 		///
+		/// `submit_ipfs_identity_signed`, `submit_rpc_ready_signed`, `submit_key_share_signed`,
+		/// `submit_decryption_ready_signed`, `submit_publish_update_result`, and
+		/// `submit_ipfs_unpin_result` carry a payload signed by the reporting offchain worker's
+		/// `AuthorityId` key (the im-online heartbeat pattern); only a signature from a current
+		/// member of `Validators` is accepted.
 		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
-			if let Call::submit_rpc_ready { .. } = call {
-				Self::validate_transaction_parameters()
-			} else if let Call::submit_ipfs_identity{ .. } = call {
-				Self::validate_transaction_parameters()
+			if let Call::submit_rpc_ready_signed { payload, signature } = call {
+				Self::validate_signed_payload(payload, signature, "iris::rpc-ready")
+			} else if let Call::submit_ipfs_identity_signed { payload, signature } = call {
+				Self::validate_signed_payload(payload, signature, "iris::identity")
+			} else if let Call::submit_key_share_signed { payload, signature } = call {
+				Self::validate_signed_payload(payload, signature, "iris::key-share")
+			} else if let Call::submit_decryption_ready_signed { payload, signature } = call {
+				Self::validate_signed_payload(payload, signature, "iris::decryption-ready")
+			} else if let Call::submit_publish_update_result { payload, signature } = call {
+				Self::validate_signed_payload(payload, signature, "iris::publish-update-result")
+			} else if let Call::submit_ipfs_unpin_result { payload, signature } = call {
+				Self::validate_signed_payload(payload, signature, "iris::unpin-result")
 			} else {
 				InvalidTransaction::Call.into()
 			}
@@ -328,11 +891,53 @@ pub mod pallet {
 		AlreadyPinned,
 		/// the node is not a candidate storage provider for some asset id
 		NotACandidate,
+		/// there is no outstanding proof-of-replication challenge for the caller and asset id
+		NoActiveChallenge,
+		/// the challenge deadline has already passed
+		ChallengeExpired,
+		/// no reward budget has been allocated for the given era
+		EraNotFound,
+		/// the era is outside of the claimable `HistoryDepth` window
+		EraTooOld,
+		/// no reward points were earned for the given era/asset id, nothing to pay out
+		NoRewardsForEra,
+		/// the caller is not an active storage provider for the given asset id
+		NotAStorageProvider,
+		/// the caller has already declared itself offline
+		AlreadyOffline,
+		/// the caller has not declared itself offline
+		NotOffline,
+		/// fewer than `T::KeyShareThreshold` key shares have been collected for this
+		/// asset/requestor pair
+		InsufficientKeyShares,
+		/// the caller does not own the asset it is trying to register as encrypted, or is not
+		/// its recorded owner when queuing a `publish_update`
+		NotAssetOwner,
+		/// the caller is not in the current validator set
+		NotAValidator,
+		/// this exact signed payload has already been processed on-chain
+		PayloadAlreadyProcessed,
 	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
 		fn offchain_worker(block_number: T::BlockNumber) {
+			// guard the whole tick behind a storage lock so a slow IPFS fetch that outlives a
+			// block can't be re-entered by the next tick and duplicate work (e.g. resubmitting
+			// the same `submit_ipfs_add_results` transaction for a `DataCommand` twice)
+			let mut lock = StorageLock::<BlockAndTime<frame_system::Pallet<T>>>::with_block_and_time_deadline(
+				IPFS_WORKER_LOCK,
+				IPFS_WORKER_LOCK_BLOCK_EXPIRATION,
+				Duration::from_millis(IPFS_WORKER_LOCK_TIMEOUT_EXPIRATION),
+			);
+			let _guard = match lock.try_lock() {
+				Ok(guard) => guard,
+				Err(_) => {
+					log::debug!("IPFS: worker is already locked by another run; skipping this tick");
+					return;
+				}
+			};
+
 			// every 5 blocks
 			if block_number % 5u32.into() == 0u32.into() {
 				if let Err(e) = Self::connection_housekeeping() {
@@ -344,12 +949,31 @@ pub mod pallet {
 				log::error!("IPFS: Encountered an error while processing data requests: {:?}", e);
 			}
 
+			// publish any queued IPNS updates each block
+			if let Err(e) = Self::handle_publish_updates() {
+				log::error!("IPFS: Encountered an error while publishing IPNS updates: {:?}", e);
+			}
+
+			// answer any outstanding proof-of-replication challenges each block
+			if let Err(e) = Self::handle_storage_challenges() {
+				log::error!("IPFS: Encountered an error while answering storage challenges: {:?}", e);
+			}
+
+			// every 5 blocks, volunteer to help pin assets that have fallen under their
+			// replication target
+			if block_number % 5u32.into() == 0u32.into() {
+				if let Err(e) = Self::replicate_under_target_assets() {
+					log::error!("IPFS: Encountered an error while replicating under-target assets: {:?}", e);
+				}
+			}
+
 			// every 5 blocks
 			if block_number % 5u32.into() == 0u32.into() {
 				if let Err(e) = Self::print_metadata() {
 					log::error!("IPFS: Encountered an error while obtaining metadata: {:?}", e);
 				}
 			}
+			// _guard is dropped here, releasing the lock
 		}
 	}
 
@@ -464,6 +1088,8 @@ pub mod pallet {
         /// * `cid`: The cid generated by the OCW
         /// * `id`: The AssetId (passed through from the create_storage_asset call)
         /// * `balance`: The balance (passed through from the create_storage_asset call)
+        /// * `content_hash`: blake2-256 of the plaintext content behind `cid`
+        /// * `content_segment_root`: Merkle root over the content's `CHALLENGE_SEGMENT_SPACE` segments
         ///
         #[pallet::weight(100)]
         pub fn submit_ipfs_add_results(
@@ -472,17 +1098,28 @@ pub mod pallet {
             cid: Vec<u8>,
             id: T::AssetId,
             balance: T::Balance,
+            content_hash: T::Hash,
+            content_segment_root: T::Hash,
         ) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			let new_origin = system::RawOrigin::Signed(who.clone()).into();
 			// creates the asset class
             <pallet_iris_assets::Pallet<T>>::submit_ipfs_add_results(
 				new_origin,
-				admin,
+				admin.clone(),
 				cid,
 				id,
 				balance,
 			)?;
+			// record the content hash and per-segment Merkle root so future PoRep challenges
+			// have something to verify against
+			<ContentHashes<T>>::insert(id.clone(), content_hash);
+			<ContentSegmentRoot<T>>::insert(id.clone(), content_segment_root);
+			// remember the admin so the offchain worker can fill in `pool_owner` when
+			// auto-enqueuing replacement providers for an under-replicated asset
+			if let Ok(admin_account) = T::Lookup::lookup(admin) {
+				<AssetOwners<T>>::insert(id.clone(), admin_account);
+			}
 			// award point to all validators
 			if let Some(active_era) = ActiveEra::<T>::get() {
 				<ErasRewardPoints<T>>::mutate(active_era.clone(), id, |era_rewards| {
@@ -502,43 +1139,68 @@ pub mod pallet {
         }
 
         /// Should only be callable by OCWs (TODO)
-        /// Submit the results of an `ipfs identity` call to be stored on chain
-        ///
-        /// * origin: a validator node
-        /// * public_key: The IPFS node's public key
-        /// * multiaddresses: A vector of multiaddresses associate with the public key
+        /// Submit the results of an `ipfs identity` call to be stored on chain, authenticated by
+        /// the submitting offchain worker's `AuthorityId` signature over `payload` (the
+        /// im-online heartbeat pattern). Only a current validator's signature is accepted.
         ///
+        /// * payload: the identity data, signed over by the reporting validator
+        /// * signature: proof that `payload.public` authored this submission
         #[pallet::weight(100)]
-        pub fn submit_ipfs_identity(
+        pub fn submit_ipfs_identity_signed(
             origin: OriginFor<T>,
-            public_key: Vec<u8>,
-            multiaddresses: Vec<OpaqueMultiaddr>,
+            payload: IdentityPayload<T::Public, T::BlockNumber>,
+            signature: T::Signature,
         ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
-            <BootstrapNodes::<T>>::insert(public_key.clone(), multiaddresses.clone());
-            <SubstrateIpfsBridge::<T>>::insert(who.clone(), public_key.clone());
-			Self::deposit_event(Event::PublishedIdentity(who.clone()));
+            ensure_none(origin)?;
+            Self::check_and_record_payload(&payload, &signature)?;
+            let who = payload.public.clone().into_account();
+            <BootstrapNodes::<T>>::insert(payload.public_key.clone(), payload.multiaddresses.clone());
+            <SubstrateIpfsBridge::<T>>::insert(who.clone(), payload.public_key.clone());
+			Self::deposit_event(Event::PublishedIdentity(who));
             Ok(())
         }
 
 		/// should only be callable by validator nodes (TODO)
-		/// 
+		///
+		/// Records `who`'s independent confirmation that `pinner` has pinned `asset_id`'s CID.
+		/// `pinner` only becomes an official storage pinner, and is only rewarded, once
+		/// `T::PinConfirmationThreshold` of its candidate set has confirmed it this era - a
+		/// single attestation is no longer enough to assert availability.
+		///
 		/// * `asset_id`: The asset id corresponding to the data that was pinned
 		/// * `pinner': The node claiming to have pinned the data
-		/// 
+		///
 		#[pallet::weight(100)]
 		pub fn submit_ipfs_pin_result(
 			origin: OriginFor<T>,
 			asset_id: T::AssetId,
 			pinner: T::AccountId,
 		) -> DispatchResult {
-			let _who = ensure_signed(origin)?;
+			let who = ensure_signed(origin)?;
+			// only a current validator's confirmation counts towards the threshold, otherwise
+			// anyone could spin up throwaway signed accounts and force quorum on their own
+			ensure!(<Validators<T>>::get().contains(&who), Error::<T>::NotAValidator);
 			// verify they are a candidate storage provider
 			let candidate_storage_providers = <QueuedStorageProviders::<T>>::get(asset_id.clone());
 			ensure!(candidate_storage_providers.contains(&pinner), Error::<T>::NotACandidate);
 			// verify not already pinning the content
 			let current_pinners = <Pinners::<T>>::get(asset_id.clone());
 			ensure!(!current_pinners.contains(&pinner), Error::<T>::AlreadyPinned);
+
+			// confirmations from the same account are idempotent
+			let confirmation_count = <PinConfirmations<T>>::mutate(asset_id.clone(), pinner.clone(), |confirmers| {
+				if !confirmers.contains(&who) {
+					confirmers.push(who);
+				}
+				confirmers.len() as u32
+			});
+			// quorum is a fraction of the candidates vying to pin this asset's CID
+			let required = (T::PinConfirmationThreshold::get() * candidate_storage_providers.len() as u32).max(1);
+			if confirmation_count < required {
+				return Ok(());
+			}
+			<PinConfirmations<T>>::remove(asset_id.clone(), pinner.clone());
+
 			// TODO: we need a better scheme for *generating* pool ids -> should always be unique (cid + owner maybe?)
 			<Pinners<T>>::mutate(asset_id.clone(), |p| {
 				p.push(pinner.clone());
@@ -548,30 +1210,33 @@ pub mod pallet {
 				SessionParticipation::<T>::mutate(active_era.clone(), |p| {
 					p.push(pinner.clone());
 				});
-				<ErasRewardPoints<T>>::mutate(active_era, asset_id, |era_rewards| {
+				<ErasRewardPoints<T>>::mutate(active_era, asset_id.clone(), |era_rewards| {
 					*era_rewards.individual.entry(pinner.clone()).or_default() += 1;
 					era_rewards.total += 1;
 				});
 			}
+			Self::deposit_event(Event::PinConfirmed(asset_id, pinner));
 			Ok(())
 		}
 
-        /// Should only be callable by OCWs (TODO)
-        /// Submit the results onchain to notify a beneficiary that their data is available: TODO: how to safely share host? spam protection on rpc endpoints?
-        ///
-        /// * `beneficiary`: The account that requested the data
-        /// * `host`: The node's host where the data has been made available (RPC endpoint)
+        /// Submit the results onchain to notify a beneficiary that their data is available,
+        /// authenticated by the submitting offchain worker's `AuthorityId` signature over
+        /// `payload`. TODO: how to safely share host? spam protection on rpc endpoints?
         ///
+        /// * payload: the asset id that is now available, signed over by the reporting validator
+        /// * signature: proof that `payload.public` authored this submission
         #[pallet::weight(100)]
-        pub fn submit_rpc_ready(
-            _origin: OriginFor<T>,
-			asset_id: T::AssetId,
+        pub fn submit_rpc_ready_signed(
+            origin: OriginFor<T>,
+			payload: RpcReadyPayload<T::Public, T::BlockNumber, T::AssetId>,
+			signature: T::Signature,
         ) -> DispatchResult {
-            // ensure_signed(origin)?;
+            ensure_none(origin)?;
+			Self::check_and_record_payload(&payload, &signature)?;
 			if let Some(active_era) = ActiveEra::<T>::get() {
-				<ErasRewardPoints<T>>::mutate(active_era.clone(), asset_id.clone(), |era_rewards| {
+				<ErasRewardPoints<T>>::mutate(active_era.clone(), payload.asset_id.clone(), |era_rewards| {
 					// reward all active storage providers
-					for k in StorageProviders::<T>::get(asset_id.clone()).into_iter() {
+					for k in StorageProviders::<T>::get(payload.asset_id.clone()).into_iter() {
 						SessionParticipation::<T>::mutate(active_era.clone(), |p| {
 							p.push(k.clone());
 						});
@@ -582,6 +1247,310 @@ pub mod pallet {
 			}
             Ok(())
         }
+
+		/// Register `asset_id` as holding Shamir-split-key encrypted content. `shares` is the
+		/// submitter's client-side `k`-of-`n` split of the content key, one opaque share per
+		/// active validator, pre-encrypted under that validator's `AuthorityId` public key so
+		/// only that validator can recover its own share.
+		///
+		/// Only the asset's owner, as recorded in `AssetOwners`, may register it.
+		#[pallet::weight(100)]
+		pub fn register_encrypted_asset(
+			origin: OriginFor<T>,
+			asset_id: T::AssetId,
+			shares: Vec<(T::AccountId, Vec<u8>)>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(
+				<AssetOwners<T>>::get(asset_id.clone()) == Some(who.clone()),
+				Error::<T>::NotAssetOwner,
+			);
+			<EncryptedAssets<T>>::insert(asset_id.clone(), ());
+			for (validator, share) in shares {
+				<ValidatorKeyShares<T>>::insert(asset_id.clone(), validator, share);
+			}
+			Self::deposit_event(Event::EncryptedAssetRegistered(who, asset_id));
+			Ok(())
+		}
+
+		/// Submit a validator's re-encrypted content-key share for `payload.requestor`,
+		/// authenticated by the submitting offchain worker's `AuthorityId` signature over
+		/// `payload`. Called once per validator per requestor, in response to an authorized
+		/// `CatBytes` request against an [`EncryptedAssets`] asset.
+		#[pallet::weight(100)]
+		pub fn submit_key_share_signed(
+			origin: OriginFor<T>,
+			payload: KeyShareSubmissionPayload<T::Public, T::BlockNumber, T::AssetId, T::AccountId>,
+			signature: T::Signature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			Self::check_and_record_payload(&payload, &signature)?;
+			let validator = payload.public.clone().into_account();
+			<RequestorKeyShares<T>>::mutate(payload.asset_id.clone(), payload.requestor.clone(), |shares| {
+				if !shares.iter().any(|(v, _)| *v == validator) {
+					shares.push((validator.clone(), payload.share.clone()));
+				}
+			});
+			Self::deposit_event(Event::KeyShareSubmitted(payload.asset_id, payload.requestor, validator));
+			Ok(())
+		}
+
+		/// Declare that `T::KeyShareThreshold` key shares have been collected for
+		/// `payload.requestor`, so it can now reconstruct the content key off-chain and decrypt
+		/// the asset locally. Rewards the contributing validators like `submit_rpc_ready_signed`
+		/// rewards storage providers.
+		#[pallet::weight(100)]
+		pub fn submit_decryption_ready_signed(
+			origin: OriginFor<T>,
+			payload: DecryptionReadyPayload<T::Public, T::BlockNumber, T::AssetId, T::AccountId>,
+			signature: T::Signature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			Self::check_and_record_payload(&payload, &signature)?;
+			let contributors = <RequestorKeyShares<T>>::get(payload.asset_id.clone(), payload.requestor.clone());
+			ensure!(
+				contributors.len() as u32 >= T::KeyShareThreshold::get(),
+				Error::<T>::InsufficientKeyShares,
+			);
+			if let Some(active_era) = ActiveEra::<T>::get() {
+				<ErasRewardPoints<T>>::mutate(active_era.clone(), payload.asset_id.clone(), |era_rewards| {
+					for (validator, _) in contributors {
+						SessionParticipation::<T>::mutate(active_era.clone(), |p| {
+							p.push(validator.clone());
+						});
+						*era_rewards.individual.entry(validator).or_default() += 1;
+						era_rewards.total += 1;
+					}
+				});
+			}
+			Self::deposit_event(Event::DecryptionReady(payload.asset_id, payload.requestor));
+			Ok(())
+		}
+
+		/// Submit the response to an outstanding proof-of-replication challenge.
+		///
+		/// * `asset_id`: The asset whose CID is being challenged
+		/// * `segment`: the challenged `segment_index`-th slice of the content's raw bytes,
+		///   in full -- not just its hash. A bare hash would be just as cheap to precompute,
+		///   once, for every one of the `CHALLENGE_SEGMENT_SPACE` possible segments as it would
+		///   be to compute for the one actually challenged, letting a provider discard the real
+		///   content and answer every future era's challenge from that small cached table
+		///   instead of proving it still holds the data. Requiring the bytes themselves forces a
+		///   fresh fetch against whichever segment `challenge.seed` happened to pick this era.
+		/// * `merkle_proof`: the sibling hashes needed to recompute `ContentSegmentRoot` from
+		///   `hash(segment)`, bottom level first
+		#[pallet::weight(100)]
+		pub fn submit_porep_proof(
+			origin: OriginFor<T>,
+			asset_id: T::AssetId,
+			segment: Vec<u8>,
+			merkle_proof: Vec<T::Hash>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let challenge = <StorageChallenges<T>>::get(asset_id.clone(), who.clone())
+				.ok_or(Error::<T>::NoActiveChallenge)?;
+			ensure!(
+				<frame_system::Pallet<T>>::block_number() <= challenge.deadline_block,
+				Error::<T>::ChallengeExpired,
+			);
+
+			let leaf = T::Hashing::hash(&segment);
+			let root = <ContentSegmentRoot<T>>::get(asset_id.clone());
+			let proof_valid = Self::verify_segment_proof(
+				leaf,
+				challenge.segment_index as usize,
+				&merkle_proof,
+				root,
+			);
+			<StorageChallenges<T>>::remove(asset_id.clone(), who.clone());
+
+			if proof_valid {
+				<ReplicationFaults<T>>::remove(asset_id.clone(), who.clone());
+				if let Some(active_era) = ActiveEra::<T>::get() {
+					SessionParticipation::<T>::mutate(active_era.clone(), |p| {
+						p.push(who.clone());
+					});
+					<ErasRewardPoints<T>>::mutate(active_era, asset_id.clone(), |era_rewards| {
+						*era_rewards.individual.entry(who.clone()).or_default() += 1;
+						era_rewards.total += 1;
+					});
+				}
+				// a digest of this response together with `challenge.seed`, computed for the
+				// debug log only. Acceptance above depends on `segment` and `merkle_proof`
+				// against `ContentSegmentRoot`; the seed enters it only through the
+				// `segment_index` it selected, and no seed-keyed encryption of the segment is
+				// checked.
+				let challenge_response = T::Hashing::hash_of(&(challenge.seed, &segment));
+				log::debug!(
+					target: LOG_TARGET,
+					"verified fresh PoRep proof from {:?} for {:?}, seed-bound response {:?}",
+					who, asset_id, challenge_response,
+				);
+				Self::deposit_event(Event::ChallengeFulfilled(who, asset_id));
+			} else {
+				<ReplicationFaults<T>>::mutate(asset_id.clone(), who.clone(), |f| *f += 1);
+				Self::deposit_event(Event::ChallengeFailed(who, asset_id));
+			}
+			Ok(())
+		}
+
+		/// Pay out a finished era's reward budget to the storage providers of `asset_id`,
+		/// proportional to the reward points each of them earned during that era.
+		///
+		/// Permissionless, like `pallet-staking`'s `payout_stakers`: anyone may trigger the
+		/// payout on a provider's behalf.
+		#[pallet::weight(100)]
+		pub fn payout_stakers(
+			origin: OriginFor<T>,
+			era: EraIndex,
+			asset_id: T::AssetId,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			if let Some(current_era) = CurrentEra::<T>::get() {
+				ensure!(
+					era.saturating_add(T::HistoryDepth::get()) >= current_era,
+					Error::<T>::EraTooOld,
+				);
+			}
+			let budget = ErasValidatorReward::<T>::get(era).ok_or(Error::<T>::EraNotFound)?;
+
+			let era_reward_points = <ErasRewardPoints<T>>::get(era, asset_id.clone());
+			ensure!(era_reward_points.total > 0, Error::<T>::NoRewardsForEra);
+
+			// `budget` is the era's *whole* reward pot, shared by every asset that earned points
+			// that era, not just `asset_id` — take this asset's proportional slice of it before
+			// splitting that slice among its own providers, so paying out once per asset in an
+			// era sums to `budget` rather than `budget` per asset.
+			//
+			// `era_total_points` is snapshotted once, on the first `payout_stakers` call for this
+			// era: every call below removes its own asset's `ErasRewardPoints` entry, so
+			// recomputing the total from `iter_prefix` on later calls would divide by a
+			// denominator that's already been shrunk by earlier payouts in the same era.
+			let era_total_points = match <ErasTotalRewardPoints<T>>::get(era) {
+				Some(total) => total,
+				None => {
+					let total = <ErasRewardPoints<T>>::iter_prefix(era)
+						.fold(0u32, |total, (_, points)| total.saturating_add(points.total));
+					<ErasTotalRewardPoints<T>>::insert(era, total);
+					total
+				},
+			};
+			let asset_budget = Perbill::from_rational(era_reward_points.total, era_total_points) * budget;
+
+			for (who, points) in era_reward_points.individual.iter() {
+				let share = Perbill::from_rational(*points, era_reward_points.total) * asset_budget;
+				T::Currency::deposit_creating(who, share);
+				Self::deposit_event(Event::Rewarded(who.clone(), share));
+			}
+
+			<ErasRewardPoints<T>>::remove(era, asset_id);
+			Ok(())
+		}
+
+		/// Queue a new CID to be published under `asset_id`'s IPNS name, which the offchain
+		/// worker carries out in `handle_publish_updates` and confirms via
+		/// `submit_publish_update_result`. Lets the asset's underlying content be versioned
+		/// without changing the asset id or its access grants.
+		#[pallet::weight(100)]
+		pub fn publish_update(
+			origin: OriginFor<T>,
+			asset_id: T::AssetId,
+			new_cid: Vec<u8>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(
+				<AssetOwners<T>>::get(asset_id.clone()) == Some(who.clone()),
+				Error::<T>::NotAssetOwner,
+			);
+			<PendingPublishUpdates<T>>::insert(asset_id.clone(), new_cid);
+			Self::deposit_event(Event::PublishUpdateQueued(asset_id, who));
+			Ok(())
+		}
+
+		/// Confirm that a queued CID was published under an asset's IPNS name, authenticated by
+		/// the submitting offchain worker's `AuthorityId` signature over `payload`. Only a
+		/// current validator's signature is accepted -- `IpnsNames` is what `CatBytes` resolves
+		/// to decide what content to serve for an asset, so an open extrinsic here would let any
+		/// signed account hijack it.
+		///
+		/// * payload: the asset id and new IPNS name, signed over by the reporting validator
+		/// * signature: proof that `payload.public` authored this submission
+		#[pallet::weight(100)]
+		pub fn submit_publish_update_result(
+			origin: OriginFor<T>,
+			payload: PublishUpdateResultPayload<T::Public, T::BlockNumber, T::AssetId>,
+			signature: T::Signature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			Self::check_and_record_payload(&payload, &signature)?;
+			<IpnsNames<T>>::insert(payload.asset_id.clone(), payload.ipns_name);
+			<PendingPublishUpdates<T>>::remove(payload.asset_id.clone());
+			Self::deposit_event(Event::PublishUpdateApplied(payload.asset_id));
+			Ok(())
+		}
+
+		/// Voluntarily leave a storage pool. Queues an IPFS unpin of the underlying CID, which
+		/// the offchain worker carries out and confirms via `submit_ipfs_unpin_result`.
+		#[pallet::weight(100)]
+		pub fn leave_storage_pool(origin: OriginFor<T>, pool_id: T::AssetId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(
+				<StorageProviders<T>>::get(pool_id.clone()).contains(&who),
+				Error::<T>::NotAStorageProvider,
+			);
+			<StorageProviders<T>>::mutate(pool_id.clone(), |sps| sps.retain(|p| p != &who));
+			<Pinners<T>>::mutate(pool_id.clone(), |ps| ps.retain(|p| p != &who));
+			// drop any outstanding PoRep challenge along with the departure: a clean,
+			// voluntary exit shouldn't leave behind a challenge that `sweep_expired_storage_challenges`
+			// later finds past-deadline and faults/slashes this account for, since they're no
+			// longer a provider and have no way to answer it.
+			<StorageChallenges<T>>::remove(pool_id.clone(), who.clone());
+			<PendingUnpins<T>>::insert(pool_id.clone(), who.clone(), ());
+			Self::deposit_event(Event::LeftStoragePool(who, pool_id));
+			Ok(())
+		}
+
+		/// Confirm that a leaving storage provider's pin has been removed from the local IPFS
+		/// node, authenticated by the submitting offchain worker's `AuthorityId` signature over
+		/// `payload` (the same pattern `chunk0-4` established for the other OCW-only calls).
+		///
+		/// * payload: the asset id and pinner being confirmed unpinned, signed over by the
+		///   reporting validator
+		/// * signature: proof that `payload.public` authored this submission
+		#[pallet::weight(100)]
+		pub fn submit_ipfs_unpin_result(
+			origin: OriginFor<T>,
+			payload: UnpinResultPayload<T::Public, T::BlockNumber, T::AssetId, T::AccountId>,
+			signature: T::Signature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			Self::check_and_record_payload(&payload, &signature)?;
+			<PendingUnpins<T>>::remove(payload.asset_id.clone(), payload.pinner.clone());
+			Self::deposit_event(Event::UnpinSuccess(payload.pinner, payload.asset_id));
+			Ok(())
+		}
+
+		/// Temporarily suspend storage-provider serving duties without risking a slashable
+		/// storage-unavailability offence.
+		#[pallet::weight(100)]
+		pub fn go_offline(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!<OfflineIntent<T>>::get(&who), Error::<T>::AlreadyOffline);
+			<OfflineIntent<T>>::insert(who.clone(), true);
+			Self::deposit_event(Event::WentOffline(who));
+			Ok(())
+		}
+
+		/// Resume storage-provider serving duties after a voluntary suspension.
+		#[pallet::weight(100)]
+		pub fn go_online(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(<OfflineIntent<T>>::get(&who), Error::<T>::NotOffline);
+			<OfflineIntent<T>>::remove(&who);
+			Self::deposit_event(Event::WentOnline(who));
+			Ok(())
+		}
 	}
 }
 
@@ -680,8 +1649,9 @@ impl<T: Config> Pallet<T> {
 			if <QueuedStorageProviders<T>>::contains_key(asset_id.clone()) {
 				let candidates = <QueuedStorageProviders<T>>::get(asset_id.clone());
 				let pinners = <Pinners<T>>::get(asset_id.clone());
-				let mut pinner_candidate_intersection = 
-					candidates.into_iter().filter(|c| pinners.contains(c)).collect::<Vec<T::AccountId>>();
+				let mut pinner_candidate_intersection = candidates.iter().cloned()
+					.filter(|c| pinners.contains(c) && !<OfflineIntent<T>>::get(c))
+					.collect::<Vec<T::AccountId>>();
 				// <StorageProviders::<T>>::insert(asset_id.clone(), pinner_candidate_intersection);
 				<StorageProviders::<T>>::mutate(asset_id.clone(), |sps| {
 					sps.append(&mut pinner_candidate_intersection);
@@ -689,6 +1659,23 @@ impl<T: Config> Pallet<T> {
 				<QueuedStorageProviders<T>>::mutate(asset_id.clone(), |qsps| {
 					*qsps = Vec::new();
 				});
+				// drop any pin-confirmation tally still outstanding for this era's candidates;
+				// unfinalized attestations don't carry over into the next era
+				for candidate in candidates {
+					<PinConfirmations<T>>::remove(asset_id.clone(), candidate);
+				}
+			}
+			// check whether this asset is still meeting its replication target (providers may
+			// have dropped below it since the last session via offence removal or voluntary exit)
+			let target = <ReplicationFactor<T>>::get(asset_id.clone())
+				.unwrap_or_else(T::DefaultReplicationFactor::get);
+			let current = <StorageProviders<T>>::get(asset_id.clone()).len() as u32;
+			if current < target {
+				let deficit = target - current;
+				<ReplicationDeficit<T>>::insert(asset_id.clone(), deficit);
+				Self::deposit_event(Event::ReplicationUnderTarget(asset_id.clone(), deficit));
+			} else {
+				<ReplicationDeficit<T>>::remove(asset_id.clone());
 			}
 		}
 	}
@@ -697,6 +1684,10 @@ impl<T: Config> Pallet<T> {
 		// for each validator that didn't participate, mark for removal
 		let partipating_validators = SessionParticipation::<T>::get(era_index.clone());
 		for acct in Validators::<T>::get() {
+			if <OfflineIntent<T>>::get(&acct) {
+				// voluntarily suspended; not penalized for going unproductive
+				continue;
+			}
 			if !partipating_validators.contains(&acct) {
 				if UnproductiveSessions::<T>::get(acct.clone()) <= T::MaxDeadSession::get() {
 					UnproductiveSessions::<T>::mutate(acct.clone(), |v| {
@@ -711,13 +1702,80 @@ impl<T: Config> Pallet<T> {
 						<Validators<T>>::put(validators);
 						log::debug!(target: LOG_TARGET, "Validator removal initiated.");
 					}
+					Self::report_storage_unavailability(acct, era_index);
 				}
 			}
 		}
 	}
 
-	fn validate_transaction_parameters() -> TransactionValidity {
-		ValidTransaction::with_tag_prefix("iris")
+	/// report a storage provider that has exceeded `MaxDeadSession` or `MaxReplicationFaults`
+	/// as a storage-unavailability offence, slash it through `T::ReportOffence`, and drop it
+	/// from the active provider sets.
+	fn report_storage_unavailability(offender: T::AccountId, era_index: EraIndex) {
+		let unproductive_sessions = UnproductiveSessions::<T>::get(offender.clone())
+			.saturating_sub(T::MaxDeadSession::get());
+		// fold in every asset's outstanding `ReplicationFaults` for this offender too, so a
+		// provider slashed for ignoring PoRep challenges is scaled the same way as one slashed
+		// for going unproductive, rather than `ReplicationFaults` sitting uncounted
+		let replication_faults = <pallet_iris_assets::Pallet<T>>::asset_ids()
+			.into_iter()
+			.fold(0u32, |total, asset_id| {
+				total.saturating_add(<ReplicationFaults<T>>::get(asset_id, offender.clone()))
+			});
+		let consecutive_faults = unproductive_sessions.saturating_add(replication_faults);
+		let offence = StorageUnavailabilityOffence {
+			session_index: era_index,
+			validator_set_count: Validators::<T>::get().len() as u32,
+			offenders: sp_std::vec![offender.clone()],
+			consecutive_faults,
+		};
+		if let Err(e) = T::ReportOffence::report_offence(Vec::new(), offence) {
+			log::debug!(target: LOG_TARGET, "Failed to report storage unavailability offence (likely a duplicate): {:?}", e);
+		}
+
+		for asset_id in <pallet_iris_assets::Pallet<T>>::asset_ids().into_iter() {
+			<StorageProviders<T>>::mutate(asset_id.clone(), |sps| sps.retain(|p| *p != offender));
+			<Pinners<T>>::mutate(asset_id.clone(), |ps| ps.retain(|p| *p != offender));
+			// already folded into this report's slash fraction; clear it so it isn't counted
+			// again the next time this offender is reported
+			<ReplicationFaults<T>>::remove(asset_id.clone(), offender.clone());
+			// the offender is no longer a provider for this asset, so any challenge still
+			// outstanding against them can never be honestly answered -- drop it rather than
+			// let `sweep_expired_storage_challenges` find it past-deadline and fault them again
+			<StorageChallenges<T>>::remove(asset_id, offender.clone());
+		}
+		Self::deposit_event(Event::StorageProviderSlashed(offender));
+	}
+
+	/// guard against replaying a signed payload: reject (without touching storage) if this exact
+	/// `(payload, signature)` pair was already recorded by an earlier call, otherwise record it.
+	/// `validate_unsigned`'s signature/validator checks alone don't stop a payload that has
+	/// already landed on-chain from being resubmitted in a later block, since nothing about the
+	/// payload changes between submissions - this on-chain seen-set closes that gap.
+	fn check_and_record_payload<P: Encode>(payload: &P, signature: &T::Signature) -> DispatchResult {
+		let key = T::Hashing::hash_of(&(payload.encode(), signature.encode()));
+		ensure!(!ProcessedPayloads::<T>::contains_key(key), Error::<T>::PayloadAlreadyProcessed);
+		ProcessedPayloads::<T>::insert(key, ());
+		Ok(())
+	}
+
+	/// verify that `payload` was signed by `signature` over the claimed public key, and that
+	/// the claimed public key belongs to a current validator, before admitting an otherwise
+	/// unsigned extrinsic.
+	fn validate_signed_payload<P: SignedPayload<T>>(
+		payload: &P,
+		signature: &T::Signature,
+		tag_prefix: &'static str,
+	) -> TransactionValidity {
+		let signer_account = payload.public().into_account();
+		if !Validators::<T>::get().contains(&signer_account) {
+			return InvalidTransaction::BadSigner.into();
+		}
+		if !SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone()) {
+			return InvalidTransaction::BadProof.into();
+		}
+		ValidTransaction::with_tag_prefix(tag_prefix)
+			.and_provides(signer_account)
 			.longevity(5)
 			.propagate(true)
 			.build()
@@ -740,6 +1798,24 @@ impl<T: Config> Pallet<T> {
 		}
     }
 	
+	/// implementation for RPC runtime API to query the embedded IPFS node's local disk usage
+	pub fn node_storage_stats() -> Option<NodeStorageStats> {
+		let deadline = Some(timestamp().add(Duration::from_millis(5_000)));
+		match Self::ipfs_request(IpfsRequest::StorageStats, deadline) {
+			Ok(IpfsResponse::StorageStats(available_storage, maximum_storage, files, total_files)) => {
+				Some(NodeStorageStats { available_storage, maximum_storage, files, total_files })
+			},
+			Ok(_) => {
+				log::error!("IPFS: unexpected response type for a StorageStats request");
+				None
+			},
+			Err(e) => {
+				log::error!("IPFS: failed to fetch local storage stats: {:?}", e);
+				None
+			},
+		}
+	}
+
 	 /// send a request to the local IPFS node; can only be called be an off-chain worker
 	 fn ipfs_request(
         req: IpfsRequest,
@@ -759,7 +1835,69 @@ impl<T: Config> Pallet<T> {
                 Error::<T>::RequestFailed
             })
     }
-	
+
+	/// for an encrypted asset's authorized `CatBytes` request: have every local validator
+	/// account take its stored `ValidatorKeyShares` entry as registered (it is not decrypted
+	/// first), apply a ChaCha20 keystream seeded with blake2-256 of `(requestor, validator)`,
+	/// and submit the result via `submit_key_share_signed`. Once enough shares have been submitted for
+	/// `requestor`, also submits `submit_decryption_ready_signed`.
+	fn contribute_key_share(asset_id: T::AssetId, requestor: T::AccountId) {
+		let signer = Signer::<T, T::AuthorityId>::all_accounts();
+		if !signer.can_sign() {
+			log::error!(
+				"No local accounts available. Consider adding one via `author_insertKey` RPC.",
+			);
+			return;
+		}
+
+		let block_number = <frame_system::Pallet<T>>::block_number();
+		let results = signer.send_unsigned_transaction(
+			|account| {
+				let validator = account.public.clone().into_account();
+				let share = <ValidatorKeyShares<T>>::get(asset_id.clone(), validator.clone())
+					.unwrap_or_default();
+				let seed = blake2_256(&(requestor.clone(), validator).encode());
+				let mut cipher = ChaCha20::new(&seed.into(), &[0u8; 12].into());
+				let mut reencrypted = share;
+				cipher.apply_keystream(&mut reencrypted);
+				KeyShareSubmissionPayload {
+					asset_id: asset_id.clone(),
+					requestor: requestor.clone(),
+					share: reencrypted,
+					block_number,
+					public: account.public.clone(),
+				}
+			},
+			|payload, signature| Call::submit_key_share_signed { payload, signature },
+		);
+		for (_, res) in &results {
+			match res {
+				Ok(()) => log::info!("Submitted a key share for asset {:?}", asset_id),
+				Err(()) => log::error!("Failed to submit key-share transaction"),
+			}
+		}
+
+		// +1 for this tick's own submission, which hasn't landed on chain yet
+		let share_count = <RequestorKeyShares<T>>::get(asset_id.clone(), requestor.clone()).len() as u32 + 1;
+		if share_count >= T::KeyShareThreshold::get() {
+			let results = signer.send_unsigned_transaction(
+				|account| DecryptionReadyPayload {
+					asset_id: asset_id.clone(),
+					requestor: requestor.clone(),
+					block_number,
+					public: account.public.clone(),
+				},
+				|payload, signature| Call::submit_decryption_ready_signed { payload, signature },
+			);
+			for (_, res) in &results {
+				match res {
+					Ok(()) => log::info!("Submitted decryption-ready for asset {:?}", asset_id),
+					Err(()) => log::error!("Failed to submit decryption-ready transaction"),
+				}
+			}
+		}
+	}
+
 	/// manage connection to the iris ipfs swarm
     ///
     /// If the node is already a bootstrap node, do nothing. Otherwise submits a signed tx 
@@ -803,18 +1941,22 @@ impl<T: Config> Pallet<T> {
                     "No local accounts available. Consider adding one via `author_insertKey` RPC.",
                 );
             }
-             
-            let results = signer.send_signed_transaction(|_account| { 
-                Call::submit_ipfs_identity {
-                    public_key: public_key.clone(),
-                    multiaddresses: addrs.clone(),
-                }
-            });
-    
+
+			let block_number = <frame_system::Pallet<T>>::block_number();
+            let results = signer.send_unsigned_transaction(
+				|account| IdentityPayload {
+					public_key: public_key.clone(),
+					multiaddresses: addrs.clone(),
+					block_number,
+					public: account.public.clone(),
+				},
+				|payload, signature| Call::submit_ipfs_identity_signed { payload, signature },
+			);
+
             for (_, res) in &results {
                 match res {
                     Ok(()) => log::info!("Submitted ipfs identity results"),
-                    Err(e) => log::error!("Failed to submit transaction: {:?}",  e),
+                    Err(()) => log::error!("Failed to submit ipfs identity transaction"),
                 }
             }
         }
@@ -860,12 +2002,16 @@ impl<T: Config> Pallet<T> {
 												"No local accounts available. Consider adding one via `author_insertKey` RPC.",
 											);
 										}
-										let results = signer.send_signed_transaction(|_account| { 
+										let content_hash = T::Hashing::hash(&data);
+										let content_segment_root = Self::segment_merkle_root(&data);
+										let results = signer.send_signed_transaction(|_account| {
 											Call::submit_ipfs_add_results{
 												admin: admin.clone(),
 												cid: new_cid.clone(),
 												id: id.clone(),
 												balance: balance.clone(),
+												content_hash,
+												content_segment_root,
 											}
 										});
 								
@@ -898,13 +2044,31 @@ impl<T: Config> Pallet<T> {
 					let expected_pub_key = <SubstrateIpfsBridge::<T>>::get(requestor.clone());
 					ensure!(public_key == expected_pub_key, Error::<T>::BadOrigin);
 
-					let cid = <pallet_iris_assets::Pallet<T>>::metadata(
-						asset_id.clone()
-					);	
+					// resolve the asset's IPNS name to its current CID, if it has been
+					// `publish_update`d at least once; otherwise fall back to the immutable CID
+					let cid = match <IpnsNames<T>>::get(asset_id.clone()) {
+						Some(name) => match Self::ipfs_request(IpfsRequest::Resolve(name), deadline) {
+							Ok(IpfsResponse::Resolve(resolved_cid)) => resolved_cid,
+							Ok(_) => unreachable!("only Resolve can be a response for that request type."),
+							Err(e) => {
+								log::error!("IPFS: failed to resolve IPNS name for asset {:?}: {:?}", asset_id, e);
+								<pallet_iris_assets::Pallet<T>>::metadata(asset_id.clone())
+							},
+						},
+						None => <pallet_iris_assets::Pallet<T>>::metadata(asset_id.clone()),
+					};
 					ensure!(
 						owner.clone() == <pallet_iris_assets::Pallet<T>>::asset_access(requestor.clone(), asset_id.clone()),
 						Error::<T>::InsufficientBalance
 					);
+
+					if <EncryptedAssets<T>>::contains_key(asset_id.clone()) {
+						// the stored CID is a ciphertext; authorization only entitles the
+						// requestor to a key share, never plaintext
+						Self::contribute_key_share(asset_id.clone(), requestor.clone());
+						continue;
+					}
+
 					match Self::ipfs_request(IpfsRequest::CatBytes(cid.clone()), deadline) {
 						Ok(IpfsResponse::CatBytes(data)) => {
 							log::info!("IPFS: Fetched data from IPFS.");
@@ -914,11 +2078,27 @@ impl<T: Config> Pallet<T> {
 								&cid,
 								&data,
 							);
-							let call = Call::submit_rpc_ready {
-								asset_id: asset_id.clone(),
-							};
-							SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
-								.map_err(|()| Error::<T>::CantCreateRequest)?;
+							let signer = Signer::<T, T::AuthorityId>::all_accounts();
+							if !signer.can_sign() {
+								log::error!(
+									"No local accounts available. Consider adding one via `author_insertKey` RPC.",
+								);
+							}
+							let block_number = <frame_system::Pallet<T>>::block_number();
+							let results = signer.send_unsigned_transaction(
+								|account| RpcReadyPayload {
+									asset_id: asset_id.clone(),
+									block_number,
+									public: account.public.clone(),
+								},
+								|payload, signature| Call::submit_rpc_ready_signed { payload, signature },
+							);
+							for (_, res) in &results {
+								match res {
+									Ok(()) => log::info!("Submitted rpc-ready results"),
+									Err(()) => log::error!("Failed to submit rpc-ready transaction"),
+								}
+							}
 						},
 						Ok(_) => unreachable!("only CatBytes can be a response for that request type."),
 						Err(e) => log::error!("IPFS: cat error: {:?}", e),
@@ -986,6 +2166,267 @@ impl<T: Config> Pallet<T> {
         );
         Ok(())
     }
+
+	/// for each asset with a `publish_update` queued, publish the new CID under the asset's
+	/// IPNS key name -- minted deterministically from the SCALE-encoded asset id the first time
+	/// an asset is published -- then confirm on-chain via `submit_publish_update_result`.
+	fn handle_publish_updates() -> Result<(), Error<T>> {
+		let deadline = Some(timestamp().add(Duration::from_millis(5_000)));
+		let signer = Signer::<T, T::AuthorityId>::all_accounts();
+		if !signer.can_sign() {
+			log::error!(
+				"No local accounts available. Consider adding one via `author_insertKey` RPC.",
+			);
+			return Ok(());
+		}
+
+		for (asset_id, new_cid) in <PendingPublishUpdates<T>>::iter() {
+			let key_name = <IpnsNames<T>>::get(asset_id.clone())
+				.unwrap_or_else(|| asset_id.encode());
+			match Self::ipfs_request(IpfsRequest::Publish(new_cid, key_name), deadline) {
+				Ok(IpfsResponse::Publish(ipns_name)) => {
+					let block_number = <frame_system::Pallet<T>>::block_number();
+					let results = signer.send_unsigned_transaction(
+						|account| PublishUpdateResultPayload {
+							asset_id: asset_id.clone(),
+							ipns_name: ipns_name.clone(),
+							block_number,
+							public: account.public.clone(),
+						},
+						|payload, signature| Call::submit_publish_update_result { payload, signature },
+					);
+					for (_, res) in &results {
+						match res {
+							Ok(()) => log::info!("Submitted publish-update result for asset {:?}", asset_id),
+							Err(()) => log::error!("Failed to submit publish-update transaction"),
+						}
+					}
+				},
+				Ok(_) => unreachable!("only Publish can be a response for that request type."),
+				Err(e) => log::error!("IPFS: publish error: {:?}", e),
+			}
+		}
+		Ok(())
+	}
+
+	/// the raw bytes of `data`'s `index`-th (of `CHALLENGE_SEGMENT_SPACE`) slice, used both to
+	/// build `ContentSegmentRoot`'s leaves at registration time and to answer a PoRep challenge
+	/// for that index later. See `merkle::segment_bytes` for the hasher-agnostic logic.
+	fn segment_bytes(data: &[u8], index: usize) -> Vec<u8> {
+		merkle::segment_bytes(data, index)
+	}
+
+	/// split `data` into `CHALLENGE_SEGMENT_SPACE` slices of `segment_bytes`'s size (trailing
+	/// slices may be shorter or empty) and hash each one, giving the leaves of the Merkle tree
+	/// committed to as `ContentSegmentRoot`.
+	fn segment_leaves(data: &[u8]) -> Vec<T::Hash> {
+		merkle::segment_leaves::<T::Hashing>(data)
+	}
+
+	/// combine pairs of Merkle tree nodes into their parent, one level at a time, until a single
+	/// root hash remains. Relies on `CHALLENGE_SEGMENT_SPACE` being a power of two so every
+	/// level divides evenly in half.
+	fn merkle_root_from_leaves(leaves: &[T::Hash]) -> T::Hash {
+		merkle::merkle_root_from_leaves::<T::Hashing>(leaves)
+	}
+
+	/// the Merkle root over the actual plaintext content behind `asset_id`'s CID, computed by
+	/// an offchain worker that has fetched it. Stored as `ContentSegmentRoot` at registration
+	/// time so `submit_porep_proof` can later verify a segment-level proof without the chain
+	/// ever needing to read the content itself.
+	fn segment_merkle_root(data: &[u8]) -> T::Hash {
+		merkle::segment_merkle_root::<T::Hashing>(data)
+	}
+
+	/// the sibling hash at each level needed to recompute `root` from the leaf at `index`,
+	/// bottom level first. `submit_porep_proof` walks this back up via `verify_segment_proof`.
+	fn segment_merkle_proof(leaves: &[T::Hash], index: usize) -> Vec<T::Hash> {
+		merkle::segment_merkle_proof::<T::Hashing>(leaves, index)
+	}
+
+	/// recompute a Merkle root from a challenged `leaf` at `index` plus its sibling `proof`,
+	/// and check it matches the root committed to at content-registration time. Only a node
+	/// that actually fetched the segment's bytes (and so can produce both the correct leaf hash
+	/// and, implicitly, a root whose siblings were derived from the real content) can pass this
+	/// -- unlike a single whole-content hash, individual leaves can't be recovered from `root`
+	/// alone, so nothing here is derivable from already-public chain state.
+	fn verify_segment_proof(leaf: T::Hash, index: usize, proof: &[T::Hash], root: T::Hash) -> bool {
+		merkle::verify_segment_proof::<T::Hashing>(leaf, index, proof, root)
+	}
+
+	/// derive a per-provider, per-era sampling seed by xor-ing a recent block hash with the
+	/// provider's account id.
+	fn derive_challenge_seed(provider: &T::AccountId) -> [u8; 32] {
+		let current_block = <frame_system::Pallet<T>>::block_number();
+		let target_block = current_block.saturating_sub(1u32.into());
+		let block_hash = <frame_system::Pallet<T>>::block_hash(target_block);
+		let mut seed = [0u8; 32];
+		for (s, b) in seed.iter_mut().zip(block_hash.as_ref()) {
+			*s = *b;
+		}
+		for (s, b) in seed.iter_mut().zip(provider.encode()) {
+			*s ^= b;
+		}
+		seed
+	}
+
+	/// issue a fresh proof-of-replication challenge to every active storage provider, for
+	/// every asset they currently serve. Called once per era, at the start of the era.
+	fn issue_storage_challenges() {
+		let deadline_block = <frame_system::Pallet<T>>::block_number() + T::ChallengeWindow::get();
+		for asset_id in <pallet_iris_assets::Pallet<T>>::asset_ids().into_iter() {
+			for provider in <StorageProviders<T>>::get(asset_id.clone()) {
+				if <OfflineIntent<T>>::get(&provider) {
+					continue;
+				}
+				let seed = Self::derive_challenge_seed(&provider);
+				let segment_index = u32::from_le_bytes([seed[0], seed[1], seed[2], seed[3]])
+					% CHALLENGE_SEGMENT_SPACE;
+				<StorageChallenges<T>>::insert(asset_id.clone(), provider.clone(), ReplicationChallenge {
+					seed,
+					segment_index,
+					deadline_block,
+				});
+				Self::deposit_event(Event::ChallengeIssued(provider, asset_id.clone()));
+			}
+		}
+	}
+
+	/// for every outstanding proof-of-replication challenge whose `deadline_block` has passed
+	/// unanswered, treat it the same as a failed `submit_porep_proof`: drop the challenge and
+	/// bump `ReplicationFaults`. Once a provider has missed `MaxReplicationFaults` in a row for
+	/// any one asset, report it as a `StorageUnavailabilityOffence`, the same as an
+	/// unproductive validator -- otherwise a provider that simply never responds is never
+	/// penalized. Called once per era, at the end of the era.
+	fn sweep_expired_storage_challenges(era_index: EraIndex) {
+		let now = <frame_system::Pallet<T>>::block_number();
+		let expired: Vec<(T::AssetId, T::AccountId)> = <StorageChallenges<T>>::iter()
+			.filter(|(_, _, challenge)| challenge.deadline_block < now)
+			.map(|(asset_id, provider, _)| (asset_id, provider))
+			.collect();
+
+		for (asset_id, provider) in expired {
+			<StorageChallenges<T>>::remove(asset_id.clone(), provider.clone());
+			let faults = <ReplicationFaults<T>>::mutate(asset_id.clone(), provider.clone(), |f| {
+				*f += 1;
+				*f
+			});
+			Self::deposit_event(Event::ChallengeFailed(provider.clone(), asset_id));
+
+			if faults >= T::MaxReplicationFaults::get() {
+				Self::report_storage_unavailability(provider, era_index);
+			}
+		}
+	}
+
+	/// respond to any outstanding proof-of-replication challenges assigned to accounts this
+	/// node controls.
+	fn handle_storage_challenges() -> Result<(), Error<T>> {
+		if !sp_io::offchain::is_validator() {
+			return Ok(());
+		}
+		let deadline = Some(timestamp().add(Duration::from_millis(5_000)));
+		let current_block = <frame_system::Pallet<T>>::block_number();
+		for (asset_id, _provider, challenge) in <StorageChallenges<T>>::iter() {
+			if challenge.deadline_block < current_block {
+				continue;
+			}
+			let signer = Signer::<T, T::AuthorityId>::all_accounts();
+			if !signer.can_sign() {
+				continue;
+			}
+
+			let cid = <pallet_iris_assets::Pallet<T>>::metadata(asset_id.clone());
+			match Self::ipfs_request(IpfsRequest::CatBytes(cid.clone()), deadline) {
+				Ok(IpfsResponse::CatBytes(data)) => {
+					let leaves = Self::segment_leaves(&data);
+					let segment = Self::segment_bytes(&data, challenge.segment_index as usize);
+					let merkle_proof = Self::segment_merkle_proof(&leaves, challenge.segment_index as usize);
+
+					// this is a no-op for local accounts that aren't `provider`: the extrinsic
+					// will simply fail to find a matching challenge for them and be dropped.
+					let results = signer.send_signed_transaction(|_account| {
+						Call::submit_porep_proof {
+							asset_id: asset_id.clone(),
+							segment: segment.clone(),
+							merkle_proof: merkle_proof.clone(),
+						}
+					});
+					for (_, res) in &results {
+						match res {
+							Ok(()) => log::info!("Submitted PoRep proof"),
+							Err(e) => log::error!("Failed to submit PoRep proof: {:?}", e),
+						}
+					}
+				},
+				Ok(_) => unreachable!("only CatBytes can be a response for that request type."),
+				Err(e) => log::error!("IPFS: cat error while answering PoRep challenge: {:?}", e),
+			}
+		}
+		Ok(())
+	}
+
+	/// for each asset that fell under its replication target in `select_candidate_storage_providers`,
+	/// have idle local accounts volunteer to pin it via the existing `join_storage_pool` flow,
+	/// prioritizing the assets with the largest shortfall first
+	fn replicate_under_target_assets() -> Result<(), Error<T>> {
+		let mut deficits: Vec<(T::AssetId, u32)> = <ReplicationDeficit<T>>::iter().collect();
+		if deficits.is_empty() {
+			return Ok(());
+		}
+		deficits.sort_by(|a, b| b.1.cmp(&a.1));
+
+		// don't volunteer this node for more pins than its local IPFS repo has room for
+		match Self::node_storage_stats() {
+			Some(stats) if stats.available_storage < T::MinFreeStorageBytes::get() => {
+				log::info!(
+					"IPFS: local node only has {:?} bytes free, below the configured minimum; \
+					 skipping replication volunteering this block",
+					stats.available_storage,
+				);
+				return Ok(());
+			},
+			Some(_) => {},
+			None => {
+				log::error!("IPFS: could not read local storage stats; skipping replication volunteering this block");
+				return Ok(());
+			},
+		}
+
+		let signer = Signer::<T, T::AuthorityId>::all_accounts();
+		if !signer.can_sign() {
+			log::error!(
+				"No local accounts available. Consider adding one via `author_insertKey` RPC.",
+			);
+			return Ok(());
+		}
+
+		for (asset_id, deficit) in deficits {
+			let owner = match <AssetOwners<T>>::get(asset_id.clone()) {
+				Some(owner) => owner,
+				// no known owner to pass through to `join_storage_pool`, nothing we can do
+				None => continue,
+			};
+			log::info!(
+				"IPFS: asset {:?} is under its replication target by {:?}; volunteering to help pin it",
+				asset_id, deficit,
+			);
+			let results = signer.send_signed_transaction(|_account| {
+				Call::join_storage_pool {
+					pool_owner: T::Lookup::unlookup(owner.clone()),
+					pool_id: asset_id.clone(),
+				}
+			});
+			for (_, res) in &results {
+				match res {
+					Ok(()) => log::info!("Submitted a request to join the storage pool for asset {:?}", asset_id),
+					// expected for accounts that are already a candidate/provider for this asset
+					Err(e) => log::debug!("Failed to submit join_storage_pool transaction: {:?}", e),
+				}
+			}
+		}
+		Ok(())
+	}
 }
 
 // Provides the new set of validators to the session module when session is
@@ -1007,11 +2448,19 @@ impl<T: Config> pallet_session::SessionManager<T::AccountId> for Pallet<T> {
 		log::info!("Ending session with index: {:?}", end_index);
 		// TODO: calculate which validators should fetch which data? not ideal really.. idk
 		Self::mark_dead_validators(end_index);
+		Self::sweep_expired_storage_challenges(end_index);
 	}
 
 	fn start_session(start_index: u32) {
 		log::info!("Starting session with index: {:?}", start_index);
-		ActiveEra::<T>::mutate(|s| *s = Some(start_index)); 
+		ActiveEra::<T>::mutate(|s| *s = Some(start_index));
+		Self::issue_storage_challenges();
+		<ErasValidatorReward<T>>::insert(start_index, T::EraPayout::get());
+		// bound storage: forget budgets that have fallen out of the claimable window
+		if let Some(stale_era) = start_index.checked_sub(T::HistoryDepth::get()) {
+			<ErasValidatorReward<T>>::remove(stale_era);
+			<ErasTotalRewardPoints<T>>::remove(stale_era);
+		}
 	}
 }
 
@@ -1060,24 +2509,52 @@ impl<T: Config> ValidatorSetWithIdentification<T::AccountId> for Pallet<T> {
 	type IdentificationOf = ValidatorOf<T>;
 }
 
-// Offence reporting and unresponsiveness management.
-impl<T: Config, O: Offence<(T::AccountId, T::AccountId)>>
-	ReportOffence<T::AccountId, (T::AccountId, T::AccountId), O> for Pallet<T>
+/// hashes the offending accounts together with the offence's time slot, used to key
+/// `KnownOffences` so a duplicate report of the same `StorageUnavailabilityOffence` in the same
+/// session is a no-op
+fn offence_report_key<T: Config>(
+	offenders: &[T::AccountId],
+	time_slot: &SessionIndex,
+) -> T::Hash {
+	T::Hashing::hash_of(&(offenders, time_slot))
+}
+
+/// the `Config::ReportOffence` instantiation `report_storage_unavailability` dispatches through:
+/// reports (and slashes) a `StorageUnavailabilityOffence`, keyed by bare `AccountId` offenders.
+impl<T: Config> ReportOffence<T::AccountId, T::AccountId, StorageUnavailabilityOffence<T::AccountId>>
+	for Pallet<T>
 {
-	fn report_offence(_reporters: Vec<T::AccountId>, offence: O) -> Result<(), OffenceError> {
+	fn report_offence(
+		_reporters: Vec<T::AccountId>,
+		offence: StorageUnavailabilityOffence<T::AccountId>,
+	) -> Result<(), OffenceError> {
 		let offenders = offence.offenders();
+		let time_slot = offence.time_slot();
+
+		if Self::is_known_offence(&offenders, &time_slot) {
+			return Err(OffenceError::DuplicateReport);
+		}
+
+		let key = offence_report_key::<T>(&offenders, &time_slot);
+
+		let slash_fraction = offence.slash_fraction(offenders.len() as u32);
+		let treasury = T::SlashTreasuryId::get().into_account();
 
-		for (v, _) in offenders.into_iter() {
-			Self::mark_for_removal(v);
+		for offender in offenders.iter().cloned() {
+			let slashable = slash_fraction * T::SlashableBond::get();
+			let slashable = slashable.min(T::Fungible::balance(&offender));
+			if let Ok(slashed) = T::Fungible::burn_from(&offender, slashable) {
+				let _ = T::Fungible::mint_into(&treasury, slashed);
+			}
+			Self::mark_for_removal(offender);
 		}
 
+		KnownOffences::<T>::insert(key, ());
+
 		Ok(())
 	}
 
-	fn is_known_offence(
-		_offenders: &[(T::AccountId, T::AccountId)],
-		_time_slot: &O::TimeSlot,
-	) -> bool {
-		false
+	fn is_known_offence(offenders: &[T::AccountId], time_slot: &SessionIndex) -> bool {
+		KnownOffences::<T>::contains_key(offence_report_key::<T>(offenders, time_slot))
 	}
 }
\ No newline at end of file
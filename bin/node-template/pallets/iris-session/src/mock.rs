@@ -0,0 +1,12 @@
+//! Unlike `iris-ledger`'s `mock.rs`, this file does not stand up a full `construct_runtime!`.
+//! `Config` here is a supertrait of `pallet_session::Config` and `pallet_iris_assets::Config`,
+//! and `pallet_iris_assets` isn't vendored alongside this pallet in this checkout -- there is no
+//! source to write an accurate `impl pallet_iris_assets::Config for Test` against, and guessing
+//! at its associated types would be worse than not testing at all. `tests.rs` instead exercises
+//! the hasher-agnostic pieces of `Pallet<T>` that were factored out into the `merkle` module
+//! specifically so they don't need a runtime -- the Merkle-tree construction and verification
+//! behind `submit_porep_proof`, which is the part the PoRep scheme's security rests on.
+
+/// the concrete hasher tests build Merkle trees and proofs over, matching what a real runtime
+/// would plug in as `T::Hashing`.
+pub(crate) use sp_runtime::traits::BlakeTwo256 as TestHashing;
@@ -0,0 +1,53 @@
+use crate::merkle;
+use crate::mock::TestHashing;
+
+const DATA: &[u8] = b"a rather small piece of content to be sharded into segments";
+
+#[test]
+fn verify_segment_proof_accepts_every_leaf_against_the_real_root() {
+	let leaves = merkle::segment_leaves::<TestHashing>(DATA);
+	let root = merkle::merkle_root_from_leaves::<TestHashing>(&leaves);
+
+	for index in 0..leaves.len() {
+		let proof = merkle::segment_merkle_proof::<TestHashing>(&leaves, index);
+		assert!(merkle::verify_segment_proof::<TestHashing>(leaves[index], index, &proof, root));
+	}
+}
+
+#[test]
+fn verify_segment_proof_rejects_a_leaf_from_the_wrong_segment() {
+	let leaves = merkle::segment_leaves::<TestHashing>(DATA);
+	let root = merkle::merkle_root_from_leaves::<TestHashing>(&leaves);
+
+	let proof = merkle::segment_merkle_proof::<TestHashing>(&leaves, 1);
+	// a proof built for index 1 must not also verify leaf 2 at index 1 -- if it did, a provider
+	// could answer any challenge with any segment's proof, regardless of which index was sampled
+	assert!(!merkle::verify_segment_proof::<TestHashing>(leaves[2], 1, &proof, root));
+}
+
+#[test]
+fn verify_segment_proof_rejects_a_leaf_not_derived_from_the_real_content() {
+	let leaves = merkle::segment_leaves::<TestHashing>(DATA);
+	let root = merkle::merkle_root_from_leaves::<TestHashing>(&leaves);
+
+	// the literal attack `submit_porep_proof`'s raw-bytes requirement defeats: answering with a
+	// leaf hash that was never actually derived by hashing the challenged segment's bytes.
+	let forged_leaf = merkle::segment_leaves::<TestHashing>(b"forged content, never fetched")[0];
+	let proof = merkle::segment_merkle_proof::<TestHashing>(&leaves, 0);
+	assert!(!merkle::verify_segment_proof::<TestHashing>(forged_leaf, 0, &proof, root));
+}
+
+#[test]
+fn segment_leaves_covers_the_full_content_with_no_gaps_or_overlaps() {
+	let segment_count = crate::CHALLENGE_SEGMENT_SPACE as usize;
+	let reassembled: Vec<u8> =
+		(0..segment_count).flat_map(|i| merkle::segment_bytes(DATA, i)).collect();
+	assert_eq!(reassembled, DATA);
+}
+
+#[test]
+fn segment_merkle_root_changes_if_the_content_changes() {
+	let root_a = merkle::segment_merkle_root::<TestHashing>(DATA);
+	let root_b = merkle::segment_merkle_root::<TestHashing>(b"a different piece of content entirely");
+	assert_ne!(root_a, root_b);
+}